@@ -10,15 +10,15 @@ _sqlite-glance()
 
     # Complete options
     if [[ ${cur} = -* ]]; then
-      opts="-h --help -V --version -w --where -n --limit --hidden"
+      opts="-h --help -V --version -w --where -n --limit --max-width --explain --hidden --attach --summary -l --list --tables --exclude-tables --sort --group --sizes --info --check --lint --estimate-counts --no-count --count-timeout"
       compgen -V COMPREPLY -W "${opts}" -- "${cur}"
       return 0
     fi
 
     # Complete paths inside file
     if [[ -f ${prev} ]]; then
-      # List tables, case-insensitively filter them against the text entered.
-      mapfile -t COMPREPLY < <(sqlite3 -init /dev/null -safe -readonly "${prev}" "SELECT name FROM pragma_table_list() WHERE schema='main'" \
+      # List tables, views, indexes & triggers, case-insensitively filter them against the text entered.
+      mapfile -t COMPREPLY < <(sqlite3 -init /dev/null -safe -readonly "${prev}" "SELECT name FROM pragma_table_list() WHERE schema='main' UNION ALL SELECT name FROM sqlite_schema WHERE type IN ('index', 'trigger')" \
           | awk -v IGNORECASE=1 -v p="${cur}" \
               'p==substr($0,0,length(p))' \
      )
@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, Result};
+use sqlparser::dialect::SQLiteDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+use yansi::Paint;
+
+use crate::table::keywords::is_keyword;
+use crate::table::{escape_identifier, get_schema_names};
+
+mod tests;
+
+/// Stand in for a collation another application defined, comparing text
+/// byte by byte like the built-in BINARY collation
+fn fallback_collation(conn: &Connection, name: &str) -> Result<()> {
+    eprintln!(
+        "{} collation {} is not available, so text is compared as BINARY",
+        "Warning:".yellow().bold(),
+        name
+    );
+    conn.create_collation(name, |a, b| a.cmp(b))
+}
+
+/// Find names in SQL which look like function calls: an unquoted name which
+/// isn't a keyword, followed by '('. This also finds table names in some
+/// statements, like `CREATE TABLE t (...)`. The REGEXP & MATCH operators call
+/// functions of those names, which applications may define.
+/// Type names like `VARCHAR(10)` come after a column name or `AS` (in CAST),
+/// and virtual table modules after `USING`, so those are left out.
+fn called_names(sql: &str) -> Vec<String> {
+    let Ok(tokens) = Tokenizer::new(&SQLiteDialect {}, sql).tokenize() else {
+        return Vec::new();
+    };
+    let tokens: Vec<Token> = tokens
+        .into_iter()
+        .filter(|t| !matches!(t, Token::Whitespace(_)))
+        .collect();
+    let mut names = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Word(w) = token else { continue };
+        if w.quote_style.is_some() {
+            continue;
+        }
+        let name = w.value.to_lowercase();
+        let is_operator = name == "regexp" || name == "match";
+        let after_name_or_as = i.checked_sub(1).is_some_and(|p| match &tokens[p] {
+            Token::Word(prev) => {
+                let prev_name = prev.value.to_lowercase();
+                prev.quote_style.is_some()
+                    || !is_keyword(&prev_name)
+                    || prev_name == "as"
+                    || prev_name == "using"
+            }
+            _ => false,
+        });
+        let is_call =
+            !is_keyword(&name) && !after_name_or_as && tokens.get(i + 1) == Some(&Token::LParen);
+        if is_operator || is_call {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Find functions used in the schema (in views, triggers, generated columns,
+/// index expressions, etc.) which aren't available on this connection
+fn missing_functions(conn: &Connection) -> Result<BTreeSet<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_function_list")?;
+    let known = stmt
+        .query_map([], |r| r.get::<_, String>(0))?
+        .map(|n| n.map(|n| n.to_lowercase()))
+        .collect::<Result<BTreeSet<_>>>()?;
+    let mut names = BTreeSet::new();
+    let mut missing = BTreeSet::new();
+    for schema in get_schema_names(conn)? {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT name, sql FROM {}.sqlite_schema",
+            escape_identifier(&schema)
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            names.insert(row.get::<_, String>(0)?.to_lowercase());
+            if let Some(sql) = row.get::<_, Option<String>>(1)? {
+                missing.extend(called_names(&sql));
+            }
+        }
+    }
+    Ok(missing
+        .into_iter()
+        .filter(|n| !known.contains(n) && !names.contains(n))
+        .collect())
+}
+
+/// Make databases from other applications usable even if they rely on
+/// collations or functions those applications define. Unknown collations
+/// compare like BINARY, and unknown functions return NULL. A warning is
+/// printed when one of these stand-ins is first used.
+/// Call this after attaching other databases, so their schemas are included.
+/// Leave out the functions when checking the database: SQLite would compare
+/// their NULLs with values stored in expression indexes, and report problems.
+pub fn register_fallbacks(conn: &Connection, functions: bool) -> Result<()> {
+    conn.collation_needed(fallback_collation)?;
+    if !functions {
+        return Ok(());
+    }
+    for name in missing_functions(conn)? {
+        let warned = AtomicBool::new(false);
+        let warn_name = name.clone();
+        // Index expressions & generated columns can only use deterministic functions
+        conn.create_scalar_function(
+            &name,
+            -1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            move |_| {
+                if !warned.swap(true, Ordering::Relaxed) {
+                    eprintln!(
+                        "{} function {}() is not available, so it gives NULL",
+                        "Warning:".yellow().bold(),
+                        warn_name
+                    );
+                }
+                Ok(None::<i64>)
+            },
+        )?;
+    }
+    Ok(())
+}
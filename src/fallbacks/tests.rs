@@ -0,0 +1,67 @@
+#![cfg(test)]
+use super::{called_names, register_fallbacks};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+
+#[test]
+fn function_names() {
+    assert_eq!(
+        called_names(
+            "CREATE VIEW v AS SELECT slugify(title), lower(x) FROM \"notes\" WHERE x IN (1)"
+        ),
+        ["slugify", "lower"]
+    );
+    assert_eq!(called_names("CREATE TABLE t (a CHECK (a > 0))"), ["t"]);
+    assert_eq!(
+        called_names("CREATE VIEW v AS SELECT * FROM t WHERE a REGEXP '^x'"),
+        ["regexp"]
+    );
+    // Type names & virtual table modules aren't functions
+    assert_eq!(
+        called_names(
+            "CREATE TABLE t (a VARCHAR(10), \"b\" UNSIGNED BIG INT(8),
+             c DECIMAL(5, 2) DEFAULT (slugify('x')))"
+        ),
+        ["t", "slugify"]
+    );
+    assert_eq!(
+        called_names("CREATE VIEW v AS SELECT CAST(a AS NVARCHAR(5)), f(b) FROM t"),
+        ["f"]
+    );
+    assert!(called_names("CREATE VIRTUAL TABLE geo USING rtree(id, x0, x1)").is_empty());
+    assert!(called_names("not ' valid").is_empty());
+}
+
+#[test]
+fn stand_ins() -> anyhow::Result<()> {
+    // A database made by another app, which defines a collation & a function
+    let uri = "file:fallbacks_stand_ins?mode=memory&cache=shared";
+    let app_conn = Connection::open(uri)?;
+    app_conn.create_collation("REVERSE", |a, b| b.cmp(a))?;
+    app_conn.create_scalar_function(
+        "shout",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(ctx.get::<String>(0)?.to_uppercase()),
+    )?;
+    app_conn.execute_batch(
+        "CREATE TABLE notes (title TEXT COLLATE REVERSE, loud AS (shout(title)));
+         INSERT INTO notes (title) VALUES ('b'), ('a'), ('c');
+         CREATE VIEW loud_notes AS SELECT shout(title) AS t FROM notes;",
+    )?;
+
+    let conn = Connection::open(uri)?;
+    assert!(conn.prepare("SELECT * FROM loud_notes").is_err());
+    register_fallbacks(&conn, true)?;
+    let mut stmt = conn.prepare("SELECT title, loud FROM notes ORDER BY title")?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<Result<Vec<(String, Option<String>)>, _>>()?;
+    assert_eq!(
+        rows,
+        [("a".into(), None), ("b".into(), None), ("c".into(), None)]
+    );
+    let t: Option<String> = conn.query_row("SELECT t FROM loud_notes", [], |r| r.get(0))?;
+    assert_eq!(t, None);
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use regex::Regex;
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Result};
+
+mod tests;
+
+/// Get a function argument as text, converting numbers. None for NULL & blobs.
+fn text_arg(ctx: &Context, idx: usize) -> Result<Option<String>> {
+    Ok(match ctx.get_raw(idx) {
+        ValueRef::Text(t) => Some(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Null | ValueRef::Blob(_) => None,
+    })
+}
+
+/// `regexp(pattern, text)`, which SQLite calls for `text REGEXP pattern`.
+/// The compiled pattern is cached while a query runs.
+fn regexp(ctx: &Context) -> Result<Option<bool>> {
+    if ctx.get_raw(0) == ValueRef::Null {
+        return Ok(None);
+    }
+    let re: Arc<Regex> = ctx.get_or_create_aux(0, |pattern| -> anyhow::Result<Regex> {
+        Ok(Regex::new(pattern.as_str()?)?)
+    })?;
+    Ok(text_arg(ctx, 1)?.map(|text| re.is_match(&text)))
+}
+
+/// Parse a UUID from 32 hex digits, ignoring hyphens & braces
+fn parse_uuid(s: &str) -> Option<[u8; 16]> {
+    let digits: Vec<u8> = s
+        .bytes()
+        .filter(|b| !matches!(b, b'-' | b'{' | b'}'))
+        .collect();
+    if digits.len() != 32 {
+        return None;
+    }
+    let mut res = [0u8; 16];
+    for (byte, pair) in res.iter_mut().zip(digits.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(res)
+}
+
+/// Get a UUID from a 16-byte blob or a text UUID
+fn uuid_arg(ctx: &Context) -> Option<[u8; 16]> {
+    match ctx.get_raw(0) {
+        ValueRef::Blob(b) => b.try_into().ok(),
+        ValueRef::Text(t) => parse_uuid(std::str::from_utf8(t).ok()?),
+        _ => None,
+    }
+}
+
+/// `uuid_str(u)` formats a UUID like 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'
+fn uuid_str(ctx: &Context) -> Result<Option<String>> {
+    Ok(uuid_arg(ctx).map(|bytes| {
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }))
+}
+
+/// `uuid_blob(u)` converts a UUID to a 16-byte blob, to compare with stored blobs
+fn uuid_blob(ctx: &Context) -> Result<Option<Vec<u8>>> {
+    Ok(uuid_arg(ctx).map(|bytes| bytes.to_vec()))
+}
+
+/// Convert days since 1970-01-01 to (year, month, day) in the Gregorian calendar.
+/// From Howard Hinnant's date algorithms.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `from_unixtime(t)` gives the UTC date & time for a Unix timestamp, like
+/// '2024-01-31 12:00:00'. Timestamps in milliseconds or microseconds are
+/// recognised by their size, as seconds would be thousands of years away.
+fn from_unixtime(ctx: &Context) -> Result<Option<String>> {
+    let t = match ctx.get_raw(0) {
+        ValueRef::Integer(i) => i as f64,
+        ValueRef::Real(f) => f,
+        _ => return Ok(None),
+    };
+    let secs = if t.abs() < 1e11 {
+        t
+    } else if t.abs() < 1e14 {
+        t / 1e3
+    } else {
+        t / 1e6
+    };
+    let secs = secs.floor() as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let time = secs.rem_euclid(86400);
+    Ok(Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        (time / 60) % 60,
+        time % 60
+    )))
+}
+
+/// Add SQL functions which are useful in `--where` clauses: `regexp` (for
+/// the REGEXP operator), `uuid_str`, `uuid_blob` and `from_unixtime`.
+/// SQLite already has `hex`, `unhex` and `datetime(t, 'unixepoch')`.
+pub fn register_functions(conn: &Connection) -> Result<()> {
+    let flags = || FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("regexp", 2, flags(), regexp)?;
+    conn.create_scalar_function("uuid_str", 1, flags(), uuid_str)?;
+    conn.create_scalar_function("uuid_blob", 1, flags(), uuid_blob)?;
+    conn.create_scalar_function("from_unixtime", 1, flags(), from_unixtime)?;
+    Ok(())
+}
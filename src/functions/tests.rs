@@ -0,0 +1,77 @@
+#![cfg(test)]
+use super::register_functions;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+fn eval(conn: &Connection, expr: &str) -> rusqlite::Result<Value> {
+    conn.query_row(&format!("SELECT {}", expr), [], |r| r.get(0))
+}
+
+#[test]
+fn regexp() -> anyhow::Result<()> {
+    let conn = Connection::open_in_memory()?;
+    register_functions(&conn)?;
+    conn.execute_batch(
+        "CREATE TABLE t (name);
+         INSERT INTO t VALUES ('alice'), ('bob'), ('anna'), (NULL), (42);",
+    )?;
+    let mut stmt = conn.prepare("SELECT name FROM t WHERE name REGEXP '^a.*e$|^4'")?;
+    let names = stmt
+        .query_map([], |r| r.get::<_, Value>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(names, [Value::Text("alice".into()), Value::Integer(42)]);
+    assert_eq!(eval(&conn, "NULL REGEXP 'a'")?, Value::Null);
+    assert!(eval(&conn, "'a' REGEXP '('").is_err());
+    Ok(())
+}
+
+#[test]
+fn uuids() -> anyhow::Result<()> {
+    let conn = Connection::open_in_memory()?;
+    register_functions(&conn)?;
+    let uuid = "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11";
+    assert_eq!(
+        eval(&conn, "uuid_str(x'a0eebc999c0b4ef8bb6d6bb9bd380a11')")?,
+        Value::Text(uuid.into())
+    );
+    assert_eq!(
+        eval(&conn, "uuid_str('{A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11}')")?,
+        Value::Text(uuid.into())
+    );
+    assert_eq!(
+        eval(&conn, &format!("hex(uuid_blob('{}'))", uuid))?,
+        Value::Text("A0EEBC999C0B4EF8BB6D6BB9BD380A11".into())
+    );
+    assert_eq!(eval(&conn, "uuid_str('not a uuid')")?, Value::Null);
+    assert_eq!(eval(&conn, "uuid_blob(x'0102')")?, Value::Null);
+    Ok(())
+}
+
+#[test]
+fn unix_times() -> anyhow::Result<()> {
+    let conn = Connection::open_in_memory()?;
+    register_functions(&conn)?;
+    for t in [
+        "1700000000",
+        "1700000000123",
+        "1700000000123456",
+        "1700000000.5",
+    ] {
+        assert_eq!(
+            eval(&conn, &format!("from_unixtime({})", t))?,
+            eval(&conn, "datetime(1700000000, 'unixepoch')")?,
+            "{}",
+            t
+        );
+    }
+    assert_eq!(
+        eval(&conn, "from_unixtime(-1)")?,
+        Value::Text("1969-12-31 23:59:59".into())
+    );
+    assert_eq!(
+        eval(&conn, "from_unixtime(951782400)")?,
+        Value::Text("2000-02-29 00:00:00".into())
+    );
+    assert_eq!(eval(&conn, "from_unixtime('soon')")?, Value::Null);
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+
+mod tests;
+
+/// Finds the version of a format from a database of that kind
+type VersionFn = fn(&Connection) -> rusqlite::Result<Option<String>>;
+
+/// A kind of database made by a particular application or format
+#[derive(Debug)]
+pub struct AppKind {
+    pub name: String,
+    /// Value of `PRAGMA application_id` which identifies this kind, if any
+    pub application_id: Option<i32>,
+    /// Tables which all exist in this kind of database
+    pub tables: Vec<String>,
+    /// Columns which those tables must have, for built-in kinds whose table
+    /// names are too common to go by alone
+    columns: &'static [(&'static str, &'static [&'static str])],
+    /// Get a version number for the format, for built-in kinds
+    version: Option<VersionFn>,
+}
+
+impl AppKind {
+    fn new(name: &str, application_id: Option<i32>, tables: &[&str]) -> AppKind {
+        AppKind {
+            name: name.to_string(),
+            application_id,
+            tables: tables.iter().map(|t| t.to_string()).collect(),
+            columns: &[],
+            version: None,
+        }
+    }
+
+    /// Does this database look like this kind? A matching application_id is
+    /// enough; otherwise all of the characteristic tables (and columns) must
+    /// exist.
+    fn matches(&self, conn: &Connection, application_id: i32) -> rusqlite::Result<bool> {
+        if application_id != 0 && self.application_id == Some(application_id) {
+            return Ok(true);
+        }
+        if self.tables.is_empty() {
+            return Ok(false);
+        }
+        for table in &self.tables {
+            let count: usize = conn.query_row(
+                "SELECT count(*) FROM pragma_table_list \
+                 WHERE schema='main' AND name=? COLLATE NOCASE",
+                [table],
+                |r| r.get(0),
+            )?;
+            if count == 0 {
+                return Ok(false);
+            }
+        }
+        for (table, columns) in self.columns {
+            for column in *columns {
+                let count: usize = conn.query_row(
+                    "SELECT count(*) FROM pragma_table_info(?, 'main') \
+                     WHERE name=? COLLATE NOCASE",
+                    [table, column],
+                    |r| r.get(0),
+                )?;
+                if count == 0 {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Describe this kind, with the format version if we can find it.
+    /// A table which only looks like the format's may not hold a version.
+    fn label(&self, conn: &Connection) -> String {
+        match self.version.and_then(|f| f(conn).ok().flatten()) {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Application IDs are often 4 ASCII characters, like 'GPKG'
+const fn four_cc(code: &[u8; 4]) -> i32 {
+    i32::from_be_bytes(*code)
+}
+
+/// GeoPackage stores its version in user_version, e.g. 10201 for 1.2.1
+fn geopackage_version(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    let v: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    Ok((v > 0).then(|| format!("{}.{}.{}", v / 10000, (v / 100) % 100, v % 100)))
+}
+
+/// MBTiles may record the spec version it follows in the metadata table
+fn mbtiles_version(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    use rusqlite::OptionalExtension;
+    conn.query_row(
+        "SELECT value FROM metadata WHERE name = 'version'",
+        [],
+        |r| r.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+}
+
+/// Kinds of database we recognise without any configuration
+fn builtin_kinds() -> Vec<AppKind> {
+    vec![
+        AppKind {
+            version: Some(geopackage_version),
+            ..AppKind::new(
+                "GeoPackage",
+                Some(four_cc(b"GPKG")),
+                &["gpkg_contents", "gpkg_spatial_ref_sys"],
+            )
+        },
+        AppKind {
+            columns: &[
+                ("metadata", &["name", "value"]),
+                (
+                    "tiles",
+                    &["zoom_level", "tile_column", "tile_row", "tile_data"],
+                ),
+            ],
+            version: Some(mbtiles_version),
+            ..AppKind::new("MBTiles", Some(four_cc(b"MPBX")), &["metadata", "tiles"])
+        },
+        AppKind::new(
+            "Fossil repository",
+            Some(0x0f055111),
+            &["blob", "delta", "event"],
+        ),
+        AppKind::new("Fossil checkout", Some(0x0f055112), &["vvar", "vfile"]),
+        AppKind::new("SQLite Archive", None, &["sqlar"]),
+        AppKind::new(
+            "Firefox places (history & bookmarks)",
+            None,
+            &["moz_places", "moz_historyvisits", "moz_bookmarks"],
+        ),
+        AppKind::new(
+            "Chrome history",
+            None,
+            &["urls", "visits", "keyword_search_terms", "meta"],
+        ),
+        AppKind::new(
+            "Anki collection",
+            None,
+            &["col", "notes", "cards", "revlog"],
+        ),
+    ]
+}
+
+/// Parse the config file describing more kinds of database, like:
+///
+/// ```ini
+/// [My app]
+/// application_id = 0x4d594150
+/// tables = users, sessions
+/// ```
+pub fn parse_config(text: &str) -> anyhow::Result<Vec<AppKind>> {
+    let mut kinds: Vec<AppKind> = Vec::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            kinds.push(AppKind::new(name.trim(), None, &[]));
+            continue;
+        }
+        let (Some(kind), Some((key, value))) = (kinds.last_mut(), line.split_once('=')) else {
+            anyhow::bail!("Line {}: expected [name] or key = value", lineno + 1);
+        };
+        let value = value.trim();
+        match key.trim() {
+            "application_id" => {
+                let id = match value.strip_prefix("0x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).map(|n| n as i32),
+                    None => value.parse(),
+                };
+                kind.application_id = Some(id.map_err(|_| {
+                    anyhow::anyhow!("Line {}: invalid application_id {}", lineno + 1, value)
+                })?);
+            }
+            "tables" => {
+                kind.tables = value
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+            other => anyhow::bail!("Line {}: unknown key {}", lineno + 1, other),
+        }
+    }
+    Ok(kinds)
+}
+
+/// Where to look for the config file: $SQLITE_GLANCE_APPS, or
+/// sqlite-glance/apps.ini in the user's config directory
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("SQLITE_GLANCE_APPS") {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(config_dir.join("sqlite-glance").join("apps.ini"))
+}
+
+/// Get all the kinds of database we know about. Kinds from the config file
+/// come first, so they take priority over the built-in ones.
+pub fn known_kinds() -> anyhow::Result<Vec<AppKind>> {
+    let mut kinds = Vec::new();
+    if let Some(path) = config_path().filter(|p| p.is_file()) {
+        let text = std::fs::read_to_string(&path)?;
+        kinds = parse_config(&text)
+            .map_err(|e| anyhow::anyhow!("Error in {}: {}", path.display(), e))?;
+    }
+    kinds.extend(builtin_kinds());
+    Ok(kinds)
+}
+
+/// Work out what application or format made this database, if we know it
+pub fn identify(conn: &Connection, kinds: &[AppKind]) -> rusqlite::Result<Option<String>> {
+    let application_id: i32 = conn.query_row("PRAGMA application_id", [], |r| r.get(0))?;
+    for kind in kinds {
+        if kind.matches(conn, application_id)? {
+            return Ok(Some(kind.label(conn)));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,83 @@
+#![cfg(test)]
+use super::{builtin_kinds, identify, parse_config};
+use rusqlite::Connection;
+
+#[test]
+fn by_application_id() -> anyhow::Result<()> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "PRAGMA application_id = 1196444487; -- 'GPKG'
+         PRAGMA user_version = 10201;",
+    )?;
+    assert_eq!(
+        identify(&conn, &builtin_kinds())?,
+        Some("GeoPackage 1.2.1".to_owned())
+    );
+    Ok(())
+}
+
+#[test]
+fn by_tables() -> anyhow::Result<()> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "CREATE TABLE metadata (name text, value text);
+         CREATE TABLE tiles (zoom_level integer, tile_column integer, tile_row integer, tile_data blob);
+         INSERT INTO metadata VALUES ('version', '1.3');",
+    )?;
+    assert_eq!(
+        identify(&conn, &builtin_kinds())?,
+        Some("MBTiles 1.3".to_owned())
+    );
+
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch("CREATE TABLE metadata (name text, value text);")?;
+    assert_eq!(identify(&conn, &builtin_kinds())?, None);
+
+    // Another app's tables, without the columns MBTiles uses
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "CREATE TABLE metadata (k, v);
+         CREATE TABLE tiles (x, y);",
+    )?;
+    assert_eq!(identify(&conn, &builtin_kinds())?, None);
+
+    // Identified by application_id, but the metadata table has no version
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "PRAGMA application_id = 1297105496; -- 'MPBX'
+         CREATE TABLE metadata (k, v);",
+    )?;
+    assert_eq!(
+        identify(&conn, &builtin_kinds())?,
+        Some("MBTiles".to_owned())
+    );
+    Ok(())
+}
+
+#[test]
+fn config() -> anyhow::Result<()> {
+    let kinds = parse_config(
+        "# Our apps
+         [Sensor logs]
+         application_id = 0x534c4f47
+         tables = readings, devices
+
+         [Settings]
+         tables = settings
+        ",
+    )?;
+    assert_eq!(kinds.len(), 2);
+    assert_eq!(kinds[0].name, "Sensor logs");
+    assert_eq!(kinds[0].application_id, Some(0x534c4f47));
+    assert_eq!(kinds[0].tables, ["readings", "devices"]);
+    assert_eq!(kinds[1].application_id, None);
+    assert_eq!(kinds[1].tables, ["settings"]);
+
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch("CREATE TABLE Settings (k, v);")?;
+    assert_eq!(identify(&conn, &kinds)?, Some("Settings".to_owned()));
+
+    assert!(parse_config("tables = x").is_err());
+    assert!(parse_config("[x]\napplication_id = nope").is_err());
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use rusqlite::Result;
+
+use crate::table::{IndexColumn, IndexInfo, Table};
+
+mod tests;
+
+/// Find common problems in the definition of one table. Virtual, shadow and
+/// system tables are skipped, as their definitions can't be changed.
+pub fn lint_table(table: &Table) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    if table.name.starts_with("sqlite_") || table.is_shadow()? || table.virtual_using()?.is_some() {
+        return Ok(warnings);
+    }
+    let columns = table.columns_info()?;
+    let mut indexes: Vec<(IndexInfo, Vec<IndexColumn>)> = Vec::new();
+    for ix in table.indexes_info()? {
+        let cols = ix.columns(&table.conn)?;
+        indexes.push((ix, cols));
+    }
+
+    let pk_cols: Vec<&str> = columns
+        .iter()
+        .filter(|c| c.pk > 0)
+        .map(|c| c.name.as_str())
+        .collect();
+    if pk_cols.is_empty() {
+        warnings.push("No PRIMARY KEY".to_string());
+    }
+    // An INTEGER PRIMARY KEY column is the rowid, so it needs no index
+    let rowid_alias = match pk_cols.as_slice() {
+        [name] if !table.is_without_row_id()? => columns
+            .iter()
+            .find(|c| c.name == *name && c.dtype.eq_ignore_ascii_case("INTEGER"))
+            .map(|c| c.name.as_str()),
+        _ => None,
+    };
+
+    for col in &columns {
+        if col.hidden != 1 && col.dtype.is_empty() {
+            warnings.push(format!("Column {} has no declared type", col.name));
+        }
+        if col.pk > 0 && table.column_constraints(&col.name)?.autoincrement {
+            warnings.push(format!(
+                "Column {} uses AUTOINCREMENT, which is rarely needed: \
+                 INTEGER PRIMARY KEY already picks new ids, with less overhead",
+                col.name
+            ));
+        }
+    }
+
+    for fk in table.foreign_key_info()?.list {
+        let is_rowid = matches!(fk.from.as_slice(), [f] if Some(f.as_str()) == rowid_alias);
+        let covered = is_rowid
+            || indexes.iter().any(|(ix, cols)| {
+                !ix.partial
+                    && cols.len() >= fk.from.len()
+                    && fk.from.iter().all(|f| {
+                        cols[..fk.from.len()]
+                            .iter()
+                            .any(|c| !c.is_expr && c.name.eq_ignore_ascii_case(f))
+                    })
+            });
+        if !covered {
+            warnings.push(format!(
+                "Foreign key ({}) to {} has no index on these columns, \
+                 so changing rows in {} must scan this table",
+                fk.from.join(", "),
+                fk.to_table,
+                fk.to_table
+            ));
+        }
+    }
+
+    // Only indexes made with CREATE INDEX can be dropped
+    for (i, (ix, cols)) in indexes.iter().enumerate() {
+        if ix.origin != "c" || ix.partial {
+            continue;
+        }
+        for (j, (other, other_cols)) in indexes.iter().enumerate() {
+            if i == j || other.partial {
+                continue;
+            }
+            if cols == other_cols {
+                // Of two identical CREATE INDEX indexes, only report one
+                let keep_this = if ix.unique != other.unique {
+                    ix.unique
+                } else {
+                    other.origin == "c" && i < j
+                };
+                if !keep_this {
+                    warnings.push(format!("Index {} duplicates {}", ix.name, other.name));
+                    break;
+                }
+            } else if !ix.unique && other_cols.starts_with(cols) {
+                warnings.push(format!(
+                    "Index {} is redundant, as its columns are the start of {}",
+                    ix.name, other.name
+                ));
+                break;
+            }
+        }
+    }
+    Ok(warnings)
+}
@@ -0,0 +1,88 @@
+#![cfg(test)]
+use std::rc::Rc;
+
+use super::lint_table;
+use crate::table::Table;
+use rusqlite::Connection;
+
+const SCHEMA: &str = r#"
+CREATE TABLE good (
+    id INTEGER PRIMARY KEY,
+    name TEXT UNIQUE
+);
+CREATE TABLE ref (
+    id INTEGER PRIMARY KEY,
+    good_id INTEGER REFERENCES good (id),
+    other_id INTEGER REFERENCES good (id)
+);
+CREATE INDEX ref_good ON ref (good_id);
+CREATE INDEX ref_good_other ON ref (good_id, other_id);
+CREATE INDEX ref_good_other_2 ON ref (good_id, other_id);
+CREATE UNIQUE INDEX good_name ON good (name);
+CREATE TABLE dup (id INTEGER PRIMARY KEY, a TEXT);
+CREATE UNIQUE INDEX dup_a ON dup (a);
+CREATE INDEX dup_a_2 ON dup (a);
+CREATE TABLE sloppy (a, b INT);
+CREATE TABLE counter (id INTEGER PRIMARY KEY AUTOINCREMENT);
+CREATE TABLE no_rowid (k TEXT PRIMARY KEY, good_id INTEGER REFERENCES good) WITHOUT ROWID;
+CREATE VIRTUAL TABLE search USING fts5(body);
+"#;
+
+fn lint(conn: &Rc<Connection>, name: &str) -> anyhow::Result<Vec<String>> {
+    Ok(lint_table(&Table::new("main", name, Rc::clone(conn)))?)
+}
+
+#[test]
+fn constraint_index_duplicate() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    assert_eq!(
+        lint(&conn, "good")?,
+        ["Index good_name duplicates sqlite_autoindex_good_1"]
+    );
+    // The non-unique index is reported, though SQLite lists it first
+    assert_eq!(lint(&conn, "dup")?, ["Index dup_a_2 duplicates dup_a"]);
+    assert!(lint(&conn, "search")?.is_empty());
+    assert!(lint(&conn, "search_data")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn indexes() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    let warnings = lint(&conn, "ref")?;
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings.contains(
+        &"Index ref_good is redundant, as its columns are the start of ref_good_other_2".to_owned()
+    ));
+    assert!(warnings.iter().any(|w| w.contains("duplicates")));
+    // other_id is the second column of the indexes, so it's not covered
+    assert!(warnings.contains(
+        &"Foreign key (other_id) to good has no index on these columns, \
+          so changing rows in good must scan this table"
+            .to_owned()
+    ));
+    Ok(())
+}
+
+#[test]
+fn columns() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    assert_eq!(
+        lint(&conn, "sloppy")?,
+        ["No PRIMARY KEY", "Column a has no declared type"]
+    );
+    assert_eq!(
+        lint(&conn, "no_rowid")?,
+        [
+            "Foreign key (good_id) to good has no index on these columns, \
+          so changing rows in good must scan this table"
+        ]
+    );
+    let warnings = lint(&conn, "counter")?;
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("AUTOINCREMENT"));
+    Ok(())
+}
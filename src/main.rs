@@ -1,25 +1,35 @@
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write as _;
-use std::io::Write as _;
+use std::io::{Read as _, Write as _};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use anyhow;
-use clap::{value_parser, Arg, ArgAction, Command};
-use comfy_table;
-use comfy_table::presets::UTF8_FULL;
+use anyhow::Context;
+use clap::{value_parser, Arg, ArgAction, ArgGroup, Command};
+use comfy_table::presets::{UTF8_FULL, UTF8_FULL_CONDENSED};
+use comfy_table::CellAlignment;
 use crossterm::tty::IsTty;
-use rusqlite;
 use rusqlite::types::Value;
 use rusqlite::{Connection, OpenFlags};
-use sqlparser::ast::{ConditionalStatements, CreateView, Statement, TriggerEvent, TriggerPeriod};
+use sqlparser::ast::{CreateView, Statement};
 use sqlparser::dialect::SQLiteDialect;
 use sqlparser::parser::Parser;
 use yansi::{Condition, Paint};
 
+mod fallbacks;
+mod functions;
+mod known_apps;
+mod lint;
 mod table;
-use table::{get_table_names, get_view_names, Table};
+mod tests;
+use table::{
+    btree_sizes, escape_identifier, find_index, find_name, get_object_type, get_schema_names,
+    get_tables, get_views, glob_match, has_table, integrity_check, sort_by_dependencies,
+    suggest_names, trigger_table_schema, ForeignKeyInfo, IndexColumn, IndexInfo, RowCount, Table,
+};
 
 fn fmt_col_names(names: &[String]) -> String {
     let mut res = String::new();
@@ -33,6 +43,44 @@ fn fmt_col_names(names: &[String]) -> String {
     res
 }
 
+/// Format the parts of a foreign key constraint after REFERENCES table (cols)
+fn fmt_fk_actions(fk_info: &ForeignKeyInfo) -> String {
+    let mut res = String::new();
+    if fk_info.on_delete != "NO ACTION" {
+        write!(res, " ON DELETE {}", fk_info.on_delete).unwrap();
+    }
+    if fk_info.on_update != "NO ACTION" {
+        write!(res, " ON UPDATE {}", fk_info.on_update).unwrap();
+    }
+    if let Some(m) = &fk_info.match_kind {
+        write!(res, " {}", m).unwrap();
+    }
+    if let Some(d) = &fk_info.deferrable {
+        write!(res, " {}", d).unwrap();
+    }
+    res
+}
+
+/// Format index columns, with their collation & sort order if not the default
+fn fmt_index_cols(cols: &[IndexColumn]) -> String {
+    let mut parts = Vec::new();
+    for c in cols {
+        let mut s = if c.is_expr {
+            c.name.clone()
+        } else {
+            format!("{}", c.name.cyan())
+        };
+        if !c.collation.eq_ignore_ascii_case("BINARY") {
+            write!(s, " COLLATE {}", c.collation).unwrap();
+        }
+        if c.desc {
+            s.push_str(" DESC");
+        }
+        parts.push(s);
+    }
+    parts.join(", ")
+}
+
 fn show_in_pager(text: &str) -> std::io::Result<()> {
     let mut pager_proc = process::Command::new("less")
         .arg("-SR")
@@ -54,7 +102,7 @@ fn to_byte_string_literal(a: impl AsRef<[u8]>) -> String {
     fn inner(bytes: &[u8]) -> String {
         let mut lit = String::from("b\"");
         for &byte in bytes {
-            if byte >= 40 && byte <= 126 {
+            if (40..=126).contains(&byte) {
                 lit.push(std::char::from_u32(byte as u32).unwrap());
             } else {
                 write!(lit, "\\x{byte:02X}").unwrap();
@@ -66,6 +114,15 @@ fn to_byte_string_literal(a: impl AsRef<[u8]>) -> String {
     inner(a.as_ref())
 }
 
+/// e.g. "1 table", "2 tables"
+fn plural(n: usize, thing: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, thing)
+    } else {
+        format!("{} {}s", n, thing)
+    }
+}
+
 fn fmt_n_bytes(n: usize) -> String {
     if n < 1024 {
         return format!("{} B", n);
@@ -81,15 +138,315 @@ fn fmt_n_bytes(n: usize) -> String {
     "huge".to_string()
 }
 
-/// Show sample rows from one SQLite table
-/// Main implementation for `sqlite-glance file.db table`
-fn inspect_table(
-    db_table: Table,
+/// Number of bytes fetched from blob values for the table view
+const BLOB_PREVIEW_BYTES: usize = 8;
+/// Number of characters fetched from text values for the table view
+const TEXT_PREVIEW_CHARS: usize = 1000;
+
+/// Limit on the width of cells in the table view
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MaxWidth {
+    /// Fit the table to the terminal, if output is going to a terminal
+    Auto,
+    /// A fixed number of characters; 0 means no limit
+    Chars(usize),
+}
+
+fn parse_max_width(s: &str) -> Result<MaxWidth, String> {
+    if s == "auto" {
+        Ok(MaxWidth::Auto)
+    } else {
+        s.parse::<usize>()
+            .map(MaxWidth::Chars)
+            .map_err(|_| "expected a number or 'auto'".to_string())
+    }
+}
+
+/// Narrowest cells will get in auto width mode
+const MIN_AUTO_CELL_WIDTH: usize = 12;
+
+impl MaxWidth {
+    /// Maximum characters per cell for a table with ncols columns, or None
+    fn cell_chars(&self, ncols: usize) -> Option<usize> {
+        match *self {
+            MaxWidth::Chars(0) => None,
+            MaxWidth::Chars(n) => Some(n),
+            MaxWidth::Auto => {
+                if !std::io::stdout().is_tty() || ncols == 0 {
+                    return None;
+                }
+                let (term_cols, _) = crossterm::terminal::size().ok()?;
+                // Each column takes 3 characters of borders & padding, plus 1 at the end
+                let avail = usize::from(term_cols).saturating_sub(3 * ncols + 1);
+                Some(std::cmp::max(avail / ncols, MIN_AUTO_CELL_WIDTH))
+            }
+        }
+    }
+}
+
+/// Show line breaks in a text value as ↵, so each row stays on one line
+fn one_line(s: &str) -> String {
+    s.replace("\r\n", "↵").replace(['\n', '\r'], "↵")
+}
+
+/// Put a long text value on one line and shorten it to max_chars, showing
+/// how long the full value is if there's room. `len` is the full length in
+/// characters.
+fn truncate_text(s: &str, len: usize, max_chars: usize) -> String {
+    let one_line = one_line(s);
+    if len <= max_chars {
+        return one_line;
+    }
+    let mut suffix = format!("… ({} chars)", len);
+    if suffix.chars().count() >= max_chars {
+        suffix = "…".to_string();
+    }
+    let keep = max_chars.saturating_sub(suffix.chars().count());
+    let start: String = one_line.chars().take(keep).collect();
+    format!("{}{}", start, suffix)
+}
+
+/// Show the start of a blob and its size in at most max_chars, if possible.
+/// `v` is the start of the blob we fetched, and `len` its full size.
+fn truncate_blob(v: &[u8], len: usize, max_chars: usize) -> String {
+    let size = fmt_n_bytes(len);
+    (0..v.len().min(7))
+        .rev()
+        .map(|n| format!("{}.. ({})", to_byte_string_literal(&v[..n]), size))
+        .find(|s| s.chars().count() <= max_chars)
+        .unwrap_or_else(|| "…".to_string())
+}
+
+/// Format a value for the table view. `len` is the full length of the value
+/// in the database, which may be longer than a text or blob value we got.
+fn fmt_value(val: Value, len: usize, max_chars: Option<usize>) -> String {
+    match val {
+        Value::Null => "".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(s) => {
+            if let Some(max_chars) = max_chars {
+                truncate_text(&s, len, max_chars)
+            } else if s.chars().count() < len {
+                format!("{}… ({} chars)", one_line(&s), len)
+            } else {
+                one_line(&s)
+            }
+        }
+        Value::Blob(v) => {
+            let full = if len <= BLOB_PREVIEW_BYTES {
+                to_byte_string_literal(&v)
+            } else {
+                format!(
+                    "{}.. ({})",
+                    to_byte_string_literal(&v[..6]),
+                    fmt_n_bytes(len)
+                )
+            };
+            match max_chars {
+                Some(max_chars) if full.chars().count() > max_chars => {
+                    truncate_blob(&v, len, max_chars)
+                }
+                _ => full,
+            }
+        }
+    }
+}
+
+/// Build the query to fetch rows for display, with one parameter for LIMIT.
+/// `columns` are (heading, SQL expression) pairs, and `from_sql` is the rest of
+/// the query after the selected columns (FROM ... WHERE ... ORDER BY ...).
+fn rows_query(columns: &[(String, String)], from_sql: &str, max_width: MaxWidth) -> String {
+    // Only fetch the start of long text & blob values, plus their length,
+    // so we don't load entire large values to display a few characters.
+    // With no width limit (--max-width 0), text is fetched in full.
+    let text_chars = match max_width {
+        MaxWidth::Chars(0) => None,
+        _ => Some(
+            max_width
+                .cell_chars(columns.len())
+                .unwrap_or(TEXT_PREVIEW_CHARS),
+        ),
+    };
+    let select_exprs: Vec<String> = columns
+        .iter()
+        .map(|(_, c)| {
+            let text_case = text_chars
+                .map(|n| format!("WHEN 'text' THEN substr({c}, 1, {n}) "))
+                .unwrap_or_default();
+            format!(
+                "CASE typeof({c}) WHEN 'blob' THEN substr({c}, 1, {BLOB_PREVIEW_BYTES}) \
+                 {text_case}ELSE {c} END, length({c})"
+            )
+        })
+        .collect();
+    format!("SELECT {} {} LIMIT ?", select_exprs.join(", "), from_sql)
+}
+
+/// Query rows and put them in a table for display. The arguments are as for
+/// `rows_query`. Returns the table and the number of rows in it.
+fn rows_table(
+    conn: &Connection,
+    columns: &[(String, String)],
+    from_sql: &str,
+    limit: &u32,
+    max_width: MaxWidth,
+) -> anyhow::Result<(comfy_table::Table, usize)> {
+    let max_chars = max_width.cell_chars(columns.len());
+    let mut stmt = conn.prepare(&rows_query(columns, from_sql, max_width))?;
+
+    let mut table = comfy_table::Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_header(columns.iter().map(|(h, _)| h));
+
+    let mut rows = stmt.query([limit])?;
+    let mut nrows: usize = 0;
+    while let Some(row) = rows.next()? {
+        let mut row_vec = Vec::new();
+        for i in 0..columns.len() {
+            let val: Value = row.get(2 * i)?;
+            let len: Option<usize> = row.get(2 * i + 1)?;
+            row_vec.push(fmt_value(val, len.unwrap_or(0), max_chars));
+        }
+        table.add_row(row_vec);
+        nrows += 1;
+    }
+    Ok((table, nrows))
+}
+
+/// Highlight the important parts of one step in a query plan: full table
+/// scans, and the name of any index used
+fn fmt_plan_detail(detail: &str) -> String {
+    for marker in [" USING COVERING INDEX ", " USING INDEX "] {
+        if let Some((before, after)) = detail.split_once(marker) {
+            let (index, rest) = after.split_at(after.find(' ').unwrap_or(after.len()));
+            return format!("{before}{marker}{}{rest}", index.bright_green().bold());
+        }
+    }
+    let is_full_scan = detail.starts_with("SCAN ")
+        && !detail.contains(" USING ")
+        && !detail.contains(" VIRTUAL TABLE ")
+        && detail != "SCAN CONSTANT ROW";
+    if is_full_scan {
+        format!("{} {}", detail.yellow().bold(), "(full scan)".yellow())
+    } else {
+        detail.to_string()
+    }
+}
+
+/// Run EXPLAIN QUERY PLAN on a query with one parameter, and write the plan
+/// as an indented tree, like the sqlite3 shell shows it.
+fn write_query_plan(
+    conn: &Connection,
+    sql: &str,
+    param: &u32,
+    output: &mut String,
+) -> anyhow::Result<()> {
+    let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {sql}"))?;
+    // (id, parent, detail); parents always come before their children
+    let steps = stmt
+        .query_map([param], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, i64>(1)?,
+                r.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    writeln!(output, "Query plan:")?;
+    fn write_children(
+        steps: &[(i64, i64, String)],
+        parent: i64,
+        prefix: &str,
+        output: &mut String,
+    ) -> std::fmt::Result {
+        let children: Vec<_> = steps.iter().filter(|s| s.1 == parent).collect();
+        for (i, (id, _, detail)) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "└─ " } else { "├─ " };
+            writeln!(output, "{prefix}{branch}{}", fmt_plan_detail(detail))?;
+            let child_prefix = format!("{prefix}{}", if last { "   " } else { "│  " });
+            write_children(steps, *id, &child_prefix, output)?;
+        }
+        Ok(())
+    }
+    write_children(&steps, 0, "  ", output)?;
+    Ok(())
+}
+
+/// Print output, using a pager if it's too big for the terminal.
+/// `width` is the widest line, if that needs to be checked.
+fn print_output(output: &str, width: Option<usize>) -> anyhow::Result<()> {
+    if std::io::stdout().is_tty() {
+        // Crude way to figure out how much space the output takes
+        let out_height = output.lines().count();
+        let (term_cols, term_rows) = crossterm::terminal::size()?;
+        if (width.unwrap_or(0) > term_cols.into()) || (out_height > term_rows.into()) {
+            show_in_pager(output)?;
+        } else {
+            println!("{}", output);
+        }
+    } else {
+        println!("{}", output);
+    }
+    Ok(())
+}
+
+/// Show sample rows from one or more SQLite tables
+/// Main implementation for `sqlite-glance file.db table` & `file.db 'pattern*'`
+fn inspect_tables(
+    db_tables: &[Table],
     filename: &Path,
     where_clause: Option<&str>,
     limit: &u32,
+    max_width: MaxWidth,
+    explain: bool,
 ) -> anyhow::Result<()> {
     let mut output = String::new();
+    let mut tbl_width = 0;
+    for (i, db_table) in db_tables.iter().enumerate() {
+        if i > 0 {
+            writeln!(output)?;
+        }
+        let width = table_data(
+            db_table,
+            filename,
+            where_clause,
+            limit,
+            max_width,
+            explain,
+            &mut output,
+        )?;
+        tbl_width = tbl_width.max(width);
+    }
+    print_output(&output, Some(tbl_width))
+}
+
+/// Get (heading, SQL expression) pairs for the columns `SELECT *` would give
+fn select_all_columns(db_table: &Table) -> anyhow::Result<Vec<(String, String)>> {
+    Ok(db_table
+        .conn
+        .prepare(&format!(
+            "SELECT * FROM {} LIMIT 0",
+            db_table.escaped_name()
+        ))?
+        .column_names()
+        .into_iter()
+        .map(|c| (c.to_string(), escape_identifier(c)))
+        .collect())
+}
+
+/// Write sample rows from one table or view to output, returning the width
+fn table_data(
+    db_table: &Table,
+    filename: &Path,
+    where_clause: Option<&str>,
+    limit: &u32,
+    max_width: MaxWidth,
+    explain: bool,
+    output: &mut String,
+) -> anyhow::Result<usize> {
     writeln!(
         output,
         "{}: {} {}",
@@ -98,54 +455,21 @@ fn inspect_table(
         db_table.obj_type()?
     )?;
 
-    let mut stmt = db_table.conn.prepare(&format!(
-        "SELECT * FROM {} {} LIMIT ?",
-        db_table.escaped_name(),
-        if let Some(w) = where_clause {
-            format!("WHERE {}", w)
-        } else {
-            "".to_string()
-        },
-    ))?;
-    let ncols = stmt.column_count();
-
-    let mut table = comfy_table::Table::new();
-    table.load_preset(UTF8_FULL).set_header(stmt.column_names());
-
-    let mut rows = stmt.query([limit])?;
-    let mut nrows: usize = 0;
-    while let Some(row) = rows.next()? {
-        let mut row_vec = Vec::new();
-        for i in 0..ncols {
-            let val: Value = row.get(i)?;
-            row_vec.push(match val {
-                Value::Null => "".to_string(),
-                Value::Integer(i) => i.to_string(),
-                Value::Real(f) => f.to_string(),
-                Value::Text(s) => s,
-                Value::Blob(v) => {
-                    if v.len() <= 8 {
-                        to_byte_string_literal(v)
-                    } else {
-                        format!(
-                            "{}.. ({})",
-                            to_byte_string_literal(&v[..6]),
-                            fmt_n_bytes(v.len())
-                        )
-                    }
-                }
-            });
-        }
-        table.add_row(row_vec);
-        nrows += 1;
-    }
+    let where_sql = if let Some(w) = where_clause {
+        format!("WHERE {}", w)
+    } else {
+        "".to_string()
+    };
+    let columns = select_all_columns(db_table)?;
+    let from_sql = format!("FROM {} {}", db_table.escaped_name(), where_sql);
+    let (table, nrows) = rows_table(&db_table.conn, &columns, &from_sql, limit, max_width)?;
     writeln!(output, "{}", table)?;
-    if let Some(w) = where_clause {
+    if where_clause.is_some() {
         let nsel: u64 = db_table.conn.query_row(
             &format!(
-                "SELECT count(*) from {} WHERE {}",
+                "SELECT count(*) from {} {}",
                 db_table.escaped_name(),
-                w
+                where_sql
             ),
             [],
             |r| r.get(0),
@@ -160,251 +484,1292 @@ fn inspect_table(
     } else {
         writeln!(output, "{} of {} rows", nrows, db_table.count_rows()?)?;
     }
+    if explain {
+        let sql = rows_query(&columns, &from_sql, max_width);
+        write_query_plan(&db_table.conn, &sql, limit, output)?;
+    }
 
-    if std::io::stdout().is_tty() {
-        // Crude way to figure out how much space the output takes
-        let out_height = output.lines().count();
-        // 2nd line (nth(1)) is top of table: ┌───┬─ ...
-        let tbl_width = output.lines().nth(1).unwrap().chars().count();
+    let tbl_width = table.lines().next().map_or(0, |l| l.chars().count());
+    Ok(tbl_width)
+}
 
-        let (term_cols, term_rows) = crossterm::terminal::size()?;
-        if (tbl_width > term_cols.into()) || (out_height > term_rows.into()) {
-            show_in_pager(&output)?;
+/// Show the definition of an index, and the first few entries in index order
+/// Main implementation for `sqlite-glance file.db index`
+fn inspect_index(
+    db_table: &Table,
+    ix: &IndexInfo,
+    filename: &Path,
+    where_clause: Option<&str>,
+    limit: &u32,
+    max_width: MaxWidth,
+    explain: bool,
+) -> anyhow::Result<()> {
+    let conn = &db_table.conn;
+    let mut output = String::new();
+    writeln!(
+        output,
+        "{}: {} {}index on {}",
+        filename.display(),
+        escape_identifier(&ix.name).bright_green().bold(),
+        if ix.unique { "UNIQUE " } else { "" },
+        db_table.escaped_name().bright_green(),
+    )?;
+    match (ix.create_sql(conn)?, ix.origin.as_str()) {
+        (Some(sql), _) => writeln!(output, "{}", sql)?,
+        (None, "pk") => writeln!(output, "Created automatically for PRIMARY KEY constraint")?,
+        (None, _) => writeln!(output, "Created automatically for UNIQUE constraint")?,
+    }
+
+    let cols = ix.columns(conn)?;
+    writeln!(output, "Columns: {}", fmt_index_cols(&cols))?;
+    if let Ok((pages, bytes)) = conn.query_row(
+        "SELECT count(*), sum(pgsize) FROM dbstat(?) WHERE name=?",
+        [&ix.schema, &ix.name],
+        |r| Ok((r.get::<_, usize>(0)?, r.get::<_, Option<usize>>(1)?)),
+    ) {
+        writeln!(
+            output,
+            "Size: {} pages, {}",
+            pages,
+            fmt_n_bytes(bytes.unwrap_or(0))
+        )?;
+    }
+
+    // Select the indexed values in the order of the index
+    let mut columns = Vec::new();
+    let mut order_by = Vec::new();
+    for c in &cols {
+        let expr = if c.name == "<rowid>" {
+            "rowid".to_string()
+        } else if c.is_expr {
+            c.name.clone()
         } else {
-            println!("{}", output);
+            escape_identifier(&c.name)
+        };
+        order_by.push(format!(
+            "{} COLLATE {}{}",
+            expr,
+            escape_identifier(&c.collation),
+            if c.desc { " DESC" } else { "" }
+        ));
+        columns.push((c.name.clone(), expr));
+    }
+    // A partial index only has entries for rows matching its WHERE clause
+    let mut conditions: Vec<String> = ix.where_clause(conn)?.into_iter().collect();
+    let where_sql = |conditions: &[String]| {
+        if conditions.is_empty() {
+            "".to_string()
+        } else {
+            format!("WHERE ({})", conditions.join(") AND ("))
         }
+    };
+    let count_entries = |conditions: &[String]| -> rusqlite::Result<u64> {
+        conn.query_row(
+            &format!(
+                "SELECT count(*) FROM {} {}",
+                db_table.escaped_name(),
+                where_sql(conditions)
+            ),
+            [],
+            |r| r.get(0),
+        )
+    };
+    let nentries = count_entries(&conditions)?;
+    conditions.extend(where_clause.map(String::from));
+    let from_sql = format!(
+        "FROM {} {} ORDER BY {}",
+        db_table.escaped_name(),
+        where_sql(&conditions),
+        order_by.join(", ")
+    );
+    let (table, nrows) = rows_table(conn, &columns, &from_sql, limit, max_width)?;
+    writeln!(output, "{}", table)?;
+    if where_clause.is_some() {
+        writeln!(
+            output,
+            "{} of {} selected entries (of {} in index)",
+            nrows,
+            count_entries(&conditions)?,
+            nentries
+        )?;
     } else {
-        println!("{}", output);
+        writeln!(output, "{} of {} entries", nrows, nentries)?;
+    }
+    if explain {
+        let sql = rows_query(&columns, &from_sql, max_width);
+        write_query_plan(conn, &sql, limit, &mut output)?;
     }
 
+    let tbl_width = table.lines().next().map_or(0, |l| l.chars().count());
+    print_output(&output, Some(tbl_width))?;
     Ok(())
 }
 
-fn inspect_schema(conn: Rc<Connection>, filename: &Path, inc_hidden: &bool) -> anyhow::Result<()> {
+/// Show the definition of a trigger
+/// Main implementation for `sqlite-glance file.db trigger`
+fn inspect_trigger(
+    conn: &Rc<Connection>,
+    schema: &str,
+    name: &str,
+    filename: &Path,
+) -> anyhow::Result<()> {
+    let (tbl_name, sql): (String, String) = conn.query_row(
+        &format!(
+            "SELECT tbl_name, sql FROM {}.sqlite_schema WHERE type='trigger' AND name=?",
+            escape_identifier(schema)
+        ),
+        [name],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let tbl_schema = trigger_table_schema(conn, schema, &sql, &tbl_name)?;
+    let table = Table::new(&tbl_schema, &tbl_name, Rc::clone(conn));
     let mut output = String::new();
-    let table_names = get_table_names(&conn, &inc_hidden)?;
     writeln!(
         output,
-        "{} — {} tables",
-        filename.display().bold(),
-        table_names.len()
+        "{}: {} trigger on {} {}",
+        filename.display(),
+        escape_identifier(name).bright_magenta().bold(),
+        table.escaped_name().bright_green(),
+        table.obj_type()?,
     )?;
-    writeln!(output)?;
+    writeln!(output, "{};", sql)?;
+    print_output(&output, None)?;
+    Ok(())
+}
 
-    for tbl in table_names {
-        let table = Table::new(&tbl, Rc::clone(&conn));
-
-        let mut cols_unique = HashSet::new(); // Columns to label UNIQUE
-        let mut cols_w_index = HashSet::new(); // 1-column indexes, not unique
-        let mut pk_cols = Vec::new(); // Columns in the primary key
-        let mut other_indexes = Vec::new(); // Indexes we'll list
-        for ix in table.indexes_info()? {
-            let cols = ix.column_names(&conn)?;
-            if ix.origin == "pk" {
-                pk_cols = cols
-            } else if cols.len() == 1 {
-                if ix.unique {
-                    cols_unique.insert(cols.get(0).unwrap().to_string());
-                } else {
-                    cols_w_index.insert(cols.get(0).unwrap().to_string());
-                }
-            } else {
-                other_indexes.push((ix, cols))
-            }
-        }
-        let nrows = table.count_rows()?;
-        let foreign_keys = table.foreign_key_info()?;
+/// Run integrity_check, or quick_check if `quick` is set, on one schema.
+/// If integrity_check fails, e.g. because an index uses a function defined by
+/// another application, this falls back to quick_check, which doesn't compare
+/// indexes with their tables. Returns the check which ran, why the full check
+/// was skipped, and the problems found.
+fn check_schema(
+    conn: &Connection,
+    schema: &str,
+    quick: bool,
+) -> anyhow::Result<(&'static str, Option<String>, Vec<String>)> {
+    if quick {
+        return Ok(("quick_check", None, integrity_check(conn, schema, true)?));
+    }
+    match integrity_check(conn, schema, false) {
+        Ok(problems) => Ok(("integrity_check", None, problems)),
+        Err(e) => Ok((
+            "quick_check",
+            Some(e.to_string()),
+            integrity_check(conn, schema, true)?,
+        )),
+    }
+}
 
-        let description = if let Some(using) = table.virtual_using()? {
-            format!("virtual table using {}", using)
-        } else if table.is_shadow()? {
-            "shadow table".to_string()
-        } else {
-            "table".to_string()
-        };
-        let tbl_attrs = {
-            let mut attrs = Vec::new();
-            if table.is_strict()? {
-                attrs.push(format!("{}", "STRICT".bold()))
-            }
-            if table.is_without_row_id()? {
-                attrs.push(format!("{}", "WITHOUT ROWID".bold()))
-                //attrs.push("WITHOUT ROWID".to_string().bold())
-            }
-            if attrs.is_empty() {
-                "".to_string()
+/// Check the database for corruption and for rows breaking foreign keys,
+/// showing the rows which do. Returns true if no problems were found.
+/// Main implementation for `sqlite-glance --check file.db`
+fn inspect_check(
+    conn: Rc<Connection>,
+    filename: &Path,
+    quick: bool,
+    filter: &TableFilter,
+    limit: &u32,
+    max_width: MaxWidth,
+) -> anyhow::Result<bool> {
+    let mut output = String::new();
+    let mut tbl_width = 0;
+    let mut ok = true;
+    for schema in get_schema_names(&conn)? {
+        if schema == "temp" {
+            continue;
+        }
+        let (pragma, skipped, problems) = check_schema(&conn, &schema, quick)?;
+        write!(
+            output,
+            "{} {}: ",
+            if schema == "main" {
+                filename.display().to_string()
             } else {
-                format!(" [{}]", attrs.join(", "))
+                schema.clone()
             }
-        };
-        writeln!(
-            output,
-            "{} {} ({} rows){}:",
-            table.escaped_name().bright_green().bold(),
-            description,
-            nrows,
-            tbl_attrs,
+            .bold(),
+            pragma,
         )?;
-
-        // Columns info
-        for col_info in table.columns_info()? {
-            if (col_info.hidden == 1) && !inc_hidden {
-                continue;
-            }
-            write!(output, "  {}", col_info.name.cyan())?;
-            if !col_info.dtype.is_empty() {
-                write!(output, " {}", col_info.dtype)?;
-            }
-            if col_info.notnull {
-                write!(output, " NOT NULL")?;
-            }
-            // Show primary key on column if it's a PK by itself.
-            // pk_cols may be empty for integer PKs.
-            if col_info.pk > 0 && pk_cols.len() <= 1 {
-                write!(output, " PRIMARY KEY")?;
-            } else if cols_unique.contains(&col_info.name) {
-                write!(output, " UNIQUE")?;
-            } else if cols_w_index.contains(&col_info.name) {
-                write!(output, " indexed")?;
-            }
-            // Show if column is a foreign key by itself
-            if let Some(fk_info) = foreign_keys.for_name(&col_info.name) {
-                write!(output, " REFERENCES {}", fk_info.to_table.bright_green())?;
-                if &fk_info.to != &[""] {
-                    write!(output, " ({})", fmt_col_names(&fk_info.to))?;
-                }
-            }
-            // Show expression for generated columns
-            if (col_info.hidden == 2) || (col_info.hidden == 3) {
-                write!(output, " AS ({})", table.get_gencol_expr(&col_info.name)?)?;
-                if col_info.hidden == 3 {
-                    write!(output, " STORED")?;
-                }
-            } else if col_info.hidden == 1 {
-                // This only comes up in virtual tables
-                write!(output, " hidden")?;
+        if problems.is_empty() {
+            writeln!(output, "{}", "ok".green())?;
+        } else {
+            ok = false;
+            writeln!(output, "{}", plural(problems.len(), "problem").red())?;
+            for problem in problems {
+                writeln!(output, "  {}", problem)?;
             }
-            writeln!(output)?;
-        }
-        if pk_cols.len() > 1 {
-            writeln!(output, "PRIMARY KEY ({})", fmt_col_names(&pk_cols))?;
         }
-
-        for fk_info in foreign_keys.multicolumn() {
+        if let Some(e) = skipped {
             writeln!(
                 output,
-                "FOREIGN KEY ({}) REFERENCES {} ({})",
-                fmt_col_names(&fk_info.from),
-                &fk_info.to_table.bright_green(),
-                fmt_col_names(&fk_info.to)
+                "  {} integrity_check failed ({}), so index contents weren't checked",
+                "Note:".yellow().bold(),
+                e
             )?;
         }
+    }
 
-        if !other_indexes.is_empty() {
-            writeln!(output, "Indexes:")?;
-            for (ix, cols) in other_indexes {
-                write!(output, "  {} ({})", ix.name, fmt_col_names(&cols))?;
-                if ix.unique {
-                    write!(output, " UNIQUE")?;
+    let mut fk_ok = true;
+    for table in filter.apply(get_tables(&conn, &false)?) {
+        let violations = match table.foreign_key_violations() {
+            Ok(v) => v,
+            Err(e) => {
+                // e.g. 'foreign key mismatch' if the parent key isn't unique
+                fk_ok = false;
+                writeln!(
+                    output,
+                    "{}: could not check foreign keys: {}",
+                    table.escaped_name().bright_green().bold(),
+                    e
+                )?;
+                continue;
+            }
+        };
+        if violations.is_empty() {
+            continue;
+        }
+        fk_ok = false;
+        let fks = table.foreign_key_info()?;
+        for (fkid, rowids) in violations {
+            writeln!(output)?;
+            write!(output, "{}", table.escaped_name().bright_green().bold())?;
+            if let Some(fk) = fks.list.get(fkid) {
+                write!(
+                    output,
+                    " ({}) REFERENCES {}",
+                    fmt_col_names(&fk.from),
+                    fk.to_table.bright_green()
+                )?;
+                if fk.to != [""] {
+                    write!(output, " ({})", fmt_col_names(&fk.to))?;
                 }
-                writeln!(output)?;
+            }
+            writeln!(
+                output,
+                ": {}",
+                format!("{} with no matching parent", plural(rowids.len(), "row")).red()
+            )?;
+            // WITHOUT ROWID tables don't give us a way to find the rows
+            let rowids: Vec<String> = rowids.iter().flatten().map(|r| r.to_string()).collect();
+            if !rowids.is_empty() {
+                let (rows, _) = rows_table(
+                    &conn,
+                    &select_all_columns(&table)?,
+                    &format!(
+                        "FROM {} WHERE rowid IN ({})",
+                        table.escaped_name(),
+                        rowids.join(", ")
+                    ),
+                    limit,
+                    max_width,
+                )?;
+                writeln!(output, "{}", rows)?;
+                tbl_width = tbl_width.max(rows.lines().next().map_or(0, |l| l.chars().count()));
             }
         }
-
-        display_triggers(&table, &mut output)?;
-        writeln!(output)?;
+    }
+    if fk_ok {
+        writeln!(output, "foreign_key_check: {}", "ok".green())?;
     }
 
-    // List views
-    for name in get_view_names(&conn)? {
-        // Views and tables are similar enough for this to work
-        let view = Table::new(&name, Rc::clone(&conn));
+    print_output(&output, Some(tbl_width))?;
+    Ok(ok && fk_ok)
+}
 
+/// Warn about common problems in table definitions.
+/// Returns true if there were no warnings.
+/// Main implementation for `sqlite-glance --lint file.db`
+fn inspect_lint(
+    conn: Rc<Connection>,
+    filename: &Path,
+    filter: &TableFilter,
+) -> anyhow::Result<bool> {
+    let mut output = String::new();
+    let mut n_warnings = 0;
+    let mut n_tables = 0;
+    for table in filter.apply(get_tables(&conn, &false)?) {
+        let warnings = lint::lint_table(&table)?;
+        if warnings.is_empty() {
+            continue;
+        }
+        writeln!(output, "{}:", table.escaped_name().bright_green().bold())?;
+        for warning in &warnings {
+            writeln!(output, "  {} {}", "⚠".yellow(), warning)?;
+        }
+        n_warnings += warnings.len();
+        n_tables += 1;
+    }
+    if n_warnings == 0 {
+        writeln!(
+            output,
+            "{}: {}",
+            filename.display().bold(),
+            "no warnings".green()
+        )?;
+    } else {
         writeln!(
             output,
-            "{} view ({} rows):",
-            view.escaped_name().bright_green().bold(),
-            view.count_rows()?
+            "{}: {} in {}",
+            filename.display().bold(),
+            plural(n_warnings, "warning"),
+            plural(n_tables, "table")
         )?;
+    }
+    print_output(&output, None)?;
+    Ok(n_warnings == 0)
+}
 
-        for col_info in view.columns_info()? {
-            writeln!(output, "  {}", col_info.name.cyan())?;
-        }
+/// Label a database made by an application or format we recognise, like
+/// ` (MBTiles 1.3)`, or an empty string
+fn fmt_app_kind(conn: &Connection) -> anyhow::Result<String> {
+    // Not knowing what made a database shouldn't stop us showing it
+    let kind = known_apps::identify(conn, &known_apps::known_kinds()?)
+        .ok()
+        .flatten();
+    Ok(match kind {
+        Some(kind) => format!(" ({})", kind.bright_blue()),
+        None => "".to_string(),
+    })
+}
 
-        // Find the 'AS SELECT' clause for this view
-        let ast = Parser::parse_sql(&SQLiteDialect {}, &view.create_sql()?)?;
-        if let Some(Statement::CreateView(CreateView { query: q, .. })) = ast.first() {
-            writeln!(output, "AS {q}")?;
-        }
+/// Read the first 100 bytes of an SQLite file, which hold the database header
+fn read_db_header(path: &Path) -> std::io::Result<[u8; 100]> {
+    let mut header = [0u8; 100];
+    std::fs::File::open(path)?.read_exact(&mut header)?;
+    Ok(header)
+}
 
-        display_triggers(&view, &mut output)?;
-        writeln!(output)?;
-    }
+/// Format a number like SQLITE_VERSION_NUMBER (3042000) as a version (3.42.0)
+fn fmt_sqlite_version(n: u32) -> String {
+    format!("{}.{}.{}", n / 1_000_000, (n / 1000) % 1000, n % 1000)
+}
 
-    if std::io::stdout().is_tty() {
-        let out_height = output.lines().count();
-        let (_, term_rows) = crossterm::terminal::size()?;
-        if out_height > term_rows.into() {
-            show_in_pager(&output)?;
-        } else {
-            println!("{}", output);
-        }
-    } else {
-        println!("{}", output);
+/// Show settings & metadata from the database header and pragmas
+/// Main implementation for `sqlite-glance --info file.db`
+fn inspect_info(conn: Rc<Connection>, path: &Path, filename: &Path) -> anyhow::Result<()> {
+    let pragma_text = |name: &str| -> rusqlite::Result<String> {
+        conn.query_row(&format!("PRAGMA {}", name), [], |r| r.get(0))
+    };
+    let pragma_int = |name: &str| -> rusqlite::Result<i64> {
+        conn.query_row(&format!("PRAGMA {}", name), [], |r| r.get(0))
+    };
+    let header = read_db_header(path)?;
+    let last_version = u32::from_be_bytes(header[96..100].try_into().unwrap());
+
+    let mut rows: Vec<(&str, String)> = vec![
+        (
+            "File size",
+            fmt_n_bytes(std::fs::metadata(path)?.len() as usize),
+        ),
+        ("Page size", pragma_int("page_size")?.to_string()),
+        ("Page count", pragma_int("page_count")?.to_string()),
+        ("Free pages", pragma_int("freelist_count")?.to_string()),
+    ];
+    let auto_vacuum = match pragma_int("auto_vacuum")? {
+        0 => "none".to_string(),
+        1 => "full".to_string(),
+        2 => "incremental".to_string(),
+        n => n.to_string(),
+    };
+    let application_id = pragma_int("application_id")?;
+    rows.extend([
+        ("Text encoding", pragma_text("encoding")?),
+        ("Journal mode", pragma_text("journal_mode")?),
+        ("Auto vacuum", auto_vacuum),
+        ("user_version", pragma_int("user_version")?.to_string()),
+        (
+            "application_id",
+            format!("{} (0x{:08x})", application_id, application_id as u32),
+        ),
+        ("schema_version", pragma_int("schema_version")?.to_string()),
+        (
+            "Last written by",
+            format!("SQLite {}", fmt_sqlite_version(last_version)),
+        ),
+    ]);
+    if let Some(kind) = known_apps::identify(&conn, &known_apps::known_kinds()?)? {
+        rows.push(("Made by", kind));
     }
 
-    Ok(())
+    let mut output = String::new();
+    writeln!(output, "{}", filename.display().bold())?;
+    let key_width = rows.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    for (key, value) in rows {
+        let key = format!("{:<width$}", key, width = key_width);
+        writeln!(output, "  {}  {}", key.cyan(), value)?;
+    }
+    print_output(&output, None)
 }
 
-fn display_triggers(table: &Table, output: &mut String) -> anyhow::Result<()> {
-    let triggers = table.triggers_info()?;
-    if triggers.is_empty() {
-        return Ok(());
-    }
-    writeln!(output, "Triggers:")?;
-    for (name, create_sql) in triggers {
-        write!(output, "  {}", name.bright_magenta())?;
-        let ast = Parser::parse_sql(&SQLiteDialect {}, &create_sql)?;
-        if let Some(Statement::CreateTrigger(ct)) = ast.first() {
-            if ct.period == Some(TriggerPeriod::After) {
-                write!(output, " AFTER")?;
-            }
-            if let Some(te) = ct.events.first() {
-                match te {
-                    TriggerEvent::Update(cols) => {
-                        let col_names: Vec<String> =
-                            cols.into_iter().map(|i| i.to_string()).collect();
-                        write!(output, " UPDATE OF {}", fmt_col_names(&col_names))?;
-                    }
-                    _ => write!(output, " {te}")?,
-                };
-            }
-            writeln!(output, "")?;
-            if let Some(ConditionalStatements::BeginEnd(bes)) = &ct.statements {
-                for stmt in &bes.statements {
-                    writeln!(output, "    {stmt};")?;
-                }
-            }
-        } else {
+/// Show the space used by each table & index
+/// Main implementation for `sqlite-glance --sizes file.db`
+fn inspect_sizes(
+    conn: Rc<Connection>,
+    filename: &Path,
+    filter: &TableFilter,
+) -> anyhow::Result<()> {
+    let mut output = String::new();
+    let mut tbl_width = 0;
+    for schema in get_schema_names(&conn)? {
+        if schema == "temp" {
+            continue;
+        }
+        let pragma = |name: &str| -> rusqlite::Result<u64> {
+            conn.query_row(
+                &format!("PRAGMA {}.{}", escape_identifier(&schema), name),
+                [],
+                |r| r.get(0),
+            )
+        };
+        let page_size = pragma("page_size")?;
+        let page_count = pragma("page_count")?;
+        let file_bytes = page_size * page_count;
+        let free_pages = pragma("freelist_count")?;
+        let sizes: Vec<_> = btree_sizes(&conn, &schema)
+            .context("Showing sizes needs the dbstat virtual table")?
+            .into_iter()
+            .map(|size| {
+                let on_table = Table::new(&schema, &size.tbl_name, Rc::clone(&conn));
+                (size, on_table)
+            })
+            .filter(|(_, on_table)| filter.matches(on_table))
+            .collect();
+        // Leave out schemas with nothing matching --tables/--exclude-tables
+        if sizes.is_empty() {
+            continue;
+        }
+        if !output.is_empty() {
+            writeln!(output)?;
+        }
+        writeln!(
+            output,
+            "{} — {}, {} pages of {} bytes, {} free pages",
+            if schema == "main" {
+                filename.display().to_string()
+            } else {
+                schema.clone()
+            }
+            .bold(),
+            fmt_n_bytes(file_bytes as usize),
+            page_count,
+            page_size,
+            free_pages,
+        )?;
+
+        let mut table = comfy_table::Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED).set_header([
+            "Name",
+            "Type",
+            "Pages",
+            "Size",
+            "Unused",
+            "Overflow pages",
+            "% of file",
+        ]);
+        for (size, on_table) in sizes {
+            let obj_type = if size.obj_type == "index" {
+                format!("index on {}", on_table.escaped_name())
+            } else {
+                size.obj_type
+            };
+            table.add_row([
+                Table::new(&schema, &size.name, Rc::clone(&conn)).escaped_name(),
+                obj_type,
+                size.pages.to_string(),
+                fmt_n_bytes(size.bytes as usize),
+                fmt_n_bytes(size.unused_bytes as usize),
+                size.overflow_pages.to_string(),
+                format!("{:.1}%", 100.0 * size.bytes as f64 / file_bytes as f64),
+            ]);
+        }
+        for column in table.column_iter_mut().skip(2) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+        writeln!(output, "{}", table)?;
+        tbl_width = tbl_width.max(table.lines().next().map_or(0, |l| l.chars().count()));
+    }
+    if output.is_empty() {
+        anyhow::bail!("No tables match the --tables & --exclude-tables patterns");
+    }
+    print_output(&output, Some(tbl_width))
+}
+
+/// Glob patterns to select which tables & views are shown
+struct TableFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TableFilter {
+    /// Patterns match the table name, or `schema.name` if they contain a '.'
+    fn matches(&self, table: &Table) -> bool {
+        let pattern_matches = |p: &String| {
+            if p.contains('.') {
+                glob_match(p, &format!("{}.{}", table.schema, table.name))
+            } else {
+                glob_match(p, &table.name)
+            }
+        };
+        (self.include.is_empty() || self.include.iter().any(pattern_matches))
+            && !self.exclude.iter().any(pattern_matches)
+    }
+
+    fn apply(&self, tables: Vec<Table>) -> Vec<Table> {
+        tables.into_iter().filter(|t| self.matches(t)).collect()
+    }
+}
+
+/// How to find the number of rows in each table for the schema views
+#[derive(Clone, Copy)]
+enum CountMode {
+    /// Count rows, falling back to an estimate after the time limit
+    Exact,
+    Estimate,
+    None,
+}
+
+struct RowCounter {
+    mode: CountMode,
+    /// When to stop counting rows, so huge databases still show quickly
+    deadline: Instant,
+    /// Counts by (schema, name), so sorting by rows doesn't count them again
+    counts: RefCell<HashMap<(String, String), RowCount>>,
+}
+
+impl RowCounter {
+    fn new(mode: CountMode, deadline: Instant) -> RowCounter {
+        RowCounter {
+            mode,
+            deadline,
+            counts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn count(&self, table: &Table) -> anyhow::Result<RowCount> {
+        let key = (table.schema.clone(), table.name.clone());
+        if let Some(&n) = self.counts.borrow().get(&key) {
+            return Ok(n);
+        }
+        let exact = match self.mode {
+            CountMode::Exact => table.count_rows_until(self.deadline)?,
+            CountMode::Estimate => None,
+            CountMode::None => return Ok(RowCount::Unknown),
+        };
+        let n = match exact {
+            Some(n) => RowCount::Exact(n),
+            None => table
+                .estimate_rows()?
+                .map_or(RowCount::Unknown, RowCount::Approx),
+        };
+        self.counts.borrow_mut().insert(key, n);
+        Ok(n)
+    }
+
+    /// Add up the rows in several tables. Tables we can't count (e.g. virtual
+    /// tables with a missing module) are left out, making the total approximate.
+    fn total(&self, tables: &[Table]) -> RowCount {
+        let mut sum = 0;
+        let mut known = false;
+        let mut exact = true;
+        for table in tables {
+            match self.count(table) {
+                Ok(RowCount::Exact(n)) => (sum, known) = (sum + n, true),
+                Ok(RowCount::Approx(n)) => (sum, known, exact) = (sum + n, true, false),
+                Ok(RowCount::Unknown) | Err(_) => exact = false,
+            }
+        }
+        if exact {
+            RowCount::Exact(sum)
+        } else if known {
+            RowCount::Approx(sum)
+        } else {
+            RowCount::Unknown
+        }
+    }
+}
+
+/// How to order & group tables in the schema view
+struct TableOrder {
+    /// name, rows, size or fk; None keeps the order SQLite lists them in
+    sort_by: Option<String>,
+    /// Group tables by the part of their name before the first '_'
+    group: bool,
+}
+
+impl TableOrder {
+    fn sort(&self, tables: Vec<Table>, counter: &RowCounter) -> anyhow::Result<Vec<Table>> {
+        Ok(match self.sort_by.as_deref() {
+            Some("name") => {
+                let mut tables = tables;
+                tables.sort_by_key(|t| t.name.to_lowercase());
+                tables
+            }
+            // Biggest first, to find where the data is
+            // Tables we can't count (e.g. virtual tables with a missing module) go last
+            Some("rows") => sort_desc_by(tables, |t| {
+                Ok(counter.count(t).ok().and_then(RowCount::value).unwrap_or(0))
+            })?,
+            Some("size") => sort_desc_by(tables, |t| {
+                t.disk_size()
+                    .context("Sorting by size needs the dbstat virtual table")
+            })?,
+            Some("fk") => sort_by_dependencies(tables)?,
+            _ => tables,
+        })
+    }
+
+    /// Sort & group tables. Returns (heading, table) pairs, where the first
+    /// table in each group has the heading for that group.
+    fn apply(
+        &self,
+        tables: Vec<Table>,
+        counter: &RowCounter,
+    ) -> anyhow::Result<Vec<(Option<String>, Table)>> {
+        let tables = self.sort(tables, counter)?;
+        if !self.group {
+            return Ok(tables.into_iter().map(|t| (None, t)).collect());
+        }
+
+        let mut groups: Vec<(Option<String>, Vec<Table>)> = Vec::new();
+        for table in tables {
+            let prefix = table
+                .name
+                .split_once('_')
+                .map(|(p, _)| format!("{}.{}", table.schema, p).to_lowercase());
+            match groups.iter_mut().find(|(p, _)| p.is_some() && *p == prefix) {
+                Some((_, group)) => group.push(table),
+                None => groups.push((prefix, vec![table])),
+            }
+        }
+        // Tables which don't share a prefix go together at the end
+        let (shared, other): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|(p, group)| p.is_some() && group.len() > 1);
+        let mut res = Vec::new();
+        for (_, group) in shared {
+            let first = &group[0];
+            let mut prefix = first.name.split_once('_').unwrap().0.to_string();
+            if first.schema != "main" {
+                prefix = format!("{}.{}", first.schema, prefix);
+            }
+            let mut heading = Some(format!("{}_* ({} tables)", prefix, group.len()));
+            for table in group {
+                res.push((heading.take(), table));
+            }
+        }
+        let other: Vec<Table> = other.into_iter().flat_map(|(_, group)| group).collect();
+        let mut heading = if res.is_empty() {
+            None
+        } else {
+            Some(format!("Other tables ({})", other.len()))
+        };
+        for table in other {
+            res.push((heading.take(), table));
+        }
+        Ok(res)
+    }
+}
+
+/// Sort tables by a (fallible) key, largest first
+fn sort_desc_by(
+    tables: Vec<Table>,
+    key: impl Fn(&Table) -> anyhow::Result<u64>,
+) -> anyhow::Result<Vec<Table>> {
+    let mut keyed = tables
+        .into_iter()
+        .map(|t| Ok((key(&t)?, t)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    keyed.sort_by_key(|(k, _)| std::cmp::Reverse(*k));
+    Ok(keyed.into_iter().map(|(_, t)| t).collect())
+}
+
+fn is_glob_pattern(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+/// 'table', 'shadow table' or 'virtual table using <module>'
+fn describe_table(table: &Table) -> anyhow::Result<String> {
+    Ok(if let Some(using) = table.virtual_using()? {
+        format!("virtual table using {}", using)
+    } else if table.is_shadow()? {
+        "shadow table".to_string()
+    } else {
+        "table".to_string()
+    })
+}
+
+/// Count the columns shown for a table or view
+fn count_columns(table: &Table, inc_hidden: &bool) -> anyhow::Result<usize> {
+    Ok(table
+        .columns_info()?
+        .iter()
+        .filter(|c| c.hidden != 1 || *inc_hidden)
+        .count())
+}
+
+/// Get the cells for one table or view in the list. If something fails, the
+/// error is shown in place of the details, and the rest of the list goes on.
+fn list_row(
+    table: &Table,
+    description: anyhow::Result<String>,
+    inc_hidden: &bool,
+    counter: &RowCounter,
+) -> Vec<String> {
+    let details = || -> anyhow::Result<Vec<String>> {
+        Ok(vec![
+            description?,
+            counter.count(table)?.to_string(),
+            count_columns(table, inc_hidden)?.to_string(),
+        ])
+    };
+    let mut row = vec![table.escaped_name()];
+    match details() {
+        Ok(cells) => row.extend(cells),
+        Err(e) => row.push(format!("Error: {}", e)),
+    }
+    row
+}
+
+/// List tables & views with one line each
+/// Main implementation for `sqlite-glance --list file.db`
+fn list_schema(
+    conn: Rc<Connection>,
+    filename: &Path,
+    inc_hidden: &bool,
+    filter: &TableFilter,
+    order: &TableOrder,
+    counter: &RowCounter,
+) -> anyhow::Result<()> {
+    let mut table = comfy_table::Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_header(["Name", "Type", "Rows", "Columns"]);
+    let tables = order.apply(filter.apply(get_tables(&conn, inc_hidden)?), counter)?;
+    let views = order.sort(filter.apply(get_views(&conn)?), counter)?;
+    for (heading, db_table) in &tables {
+        if let Some(heading) = heading {
+            table.add_row([heading.bold().to_string()]);
+        }
+        table.add_row(list_row(
+            db_table,
+            describe_table(db_table),
+            inc_hidden,
+            counter,
+        ));
+    }
+    for view in &views {
+        table.add_row(list_row(view, Ok("view".to_string()), inc_hidden, counter));
+    }
+    for column in table.column_iter_mut().skip(2) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+
+    let output = format!(
+        "{}{} — {}, {}\n{}",
+        filename.display().bold(),
+        fmt_app_kind(&conn)?,
+        plural(tables.len(), "table"),
+        plural(views.len(), "view"),
+        table
+    );
+    let tbl_width = table.lines().next().map_or(0, |l| l.chars().count());
+    print_output(&output, Some(tbl_width))
+}
+
+fn inspect_schema(
+    conn: Rc<Connection>,
+    filename: &Path,
+    inc_hidden: &bool,
+    filter: &TableFilter,
+    order: &TableOrder,
+    counter: &RowCounter,
+) -> anyhow::Result<()> {
+    let mut output = String::new();
+    let tables = order.apply(filter.apply(get_tables(&conn, inc_hidden)?), counter)?;
+    writeln!(
+        output,
+        "{}{} — {} tables",
+        filename.display().bold(),
+        fmt_app_kind(&conn)?,
+        tables.len()
+    )?;
+    writeln!(output)?;
+
+    for (heading, table) in tables {
+        if let Some(heading) = heading {
+            writeln!(output, "{}", heading.bold().underline())?;
+            writeln!(output)?;
+        }
+        let mut section = String::new();
+        let res = write_table_schema(&table, inc_hidden, counter, &mut section);
+        push_section(&mut output, &table, section, res)?;
+    }
+
+    // List views
+    for view in order.sort(filter.apply(get_views(&conn)?), counter)? {
+        let mut section = String::new();
+        let res = write_view_schema(&view, counter, &mut section);
+        push_section(&mut output, &view, section, res)?;
+    }
+
+    print_output(&output, None)?;
+
+    Ok(())
+}
+
+/// Add one table or view to the schema output. If describing it failed, the
+/// error is shown in its place, so one broken object doesn't hide the rest.
+fn push_section(
+    output: &mut String,
+    table: &Table,
+    section: String,
+    res: anyhow::Result<()>,
+) -> std::fmt::Result {
+    let failed = res.is_err();
+    if failed && section.is_empty() {
+        writeln!(output, "{}:", table.escaped_name().bright_green().bold())?;
+    }
+    output.push_str(&section);
+    if let Err(e) = res {
+        writeln!(output, "  {} {}", "Error:".red().bold(), e)?;
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+/// Describe one table for the schema view
+fn write_table_schema(
+    table: &Table,
+    inc_hidden: &bool,
+    counter: &RowCounter,
+    output: &mut String,
+) -> anyhow::Result<()> {
+    let conn = &table.conn;
+    let mut cols_unique = HashSet::new(); // Columns to label UNIQUE
+    let mut cols_w_index = HashSet::new(); // 1-column indexes, not unique
+    let mut pk_cols = Vec::new(); // Columns in the primary key
+    let mut other_indexes = Vec::new(); // Indexes we'll list
+    for ix in table.indexes_info()? {
+        if ix.origin == "pk" {
+            pk_cols = ix.column_names(conn)?;
+            continue;
+        }
+        let cols = ix.columns(conn)?;
+        let is_plain = match cols.as_slice() {
+            [c] => c.is_plain(table.column_constraints(&c.name)?.collation.as_deref()),
+            _ => false,
+        };
+        if is_plain && !ix.partial {
+            let col_name = cols.into_iter().next().unwrap().name;
+            if ix.unique {
+                cols_unique.insert(col_name);
+            } else {
+                cols_w_index.insert(col_name);
+            }
+        } else {
+            other_indexes.push((ix, cols))
+        }
+    }
+    let nrows = counter.count(table)?;
+    let foreign_keys = table.foreign_key_info()?;
+
+    let description = describe_table(table)?;
+    let tbl_attrs = {
+        let mut attrs = Vec::new();
+        if table.is_strict()? {
+            attrs.push(format!("{}", "STRICT".bold()))
+        }
+        if table.is_without_row_id()? {
+            attrs.push(format!("{}", "WITHOUT ROWID".bold()))
+            //attrs.push("WITHOUT ROWID".to_string().bold())
+        }
+        if attrs.is_empty() {
+            "".to_string()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        }
+    };
+    writeln!(
+        output,
+        "{} {} ({} rows){}:",
+        table.escaped_name().bright_green().bold(),
+        description,
+        nrows,
+        tbl_attrs,
+    )?;
+
+    // Columns info
+    for col_info in table.columns_info()? {
+        if (col_info.hidden == 1) && !inc_hidden {
+            continue;
+        }
+        let constraints = table.column_constraints(&col_info.name)?;
+        write!(output, "  {}", col_info.name.cyan())?;
+        if !col_info.dtype.is_empty() {
+            write!(output, " {}", col_info.dtype)?;
+        }
+        if col_info.notnull {
+            write!(output, " NOT NULL")?;
+            if let Some(oc) = &constraints.not_null_conflict {
+                write!(output, " {oc}")?;
+            }
+        }
+        if let Some(default) = col_info.default_sql() {
+            write!(output, " DEFAULT {}", default)?;
+        }
+        if let Some(collation) = &constraints.collation {
+            write!(output, " COLLATE {}", collation)?;
+        }
+        // Show primary key on column if it's a PK by itself.
+        // pk_cols may be empty for integer PKs.
+        if col_info.pk > 0 && pk_cols.len() <= 1 {
+            write!(output, " PRIMARY KEY")?;
+            if constraints.autoincrement {
+                write!(output, " AUTOINCREMENT")?;
+            }
+            if let Some(oc) = &constraints.pk_conflict {
+                write!(output, " {oc}")?;
+            }
+        } else if cols_unique.contains(&col_info.name) {
+            write!(output, " UNIQUE")?;
+            if let Some(oc) = &constraints.unique_conflict {
+                write!(output, " {oc}")?;
+            }
+        } else if cols_w_index.contains(&col_info.name) {
+            write!(output, " indexed")?;
+        }
+        for check in &constraints.checks {
+            write!(output, " CHECK ({})", check)?;
+        }
+        // Show if column is a foreign key by itself
+        if let Some(fk_info) = foreign_keys.for_name(&col_info.name) {
+            write!(output, " REFERENCES {}", fk_info.to_table.bright_green())?;
+            if fk_info.to != [""] {
+                write!(output, " ({})", fmt_col_names(&fk_info.to))?;
+            }
+            write!(output, "{}", fmt_fk_actions(&fk_info))?;
+        }
+        // Show expression for generated columns
+        if (col_info.hidden == 2) || (col_info.hidden == 3) {
+            write!(output, " AS ({})", table.get_gencol_expr(&col_info.name)?)?;
+            if col_info.hidden == 3 {
+                write!(output, " STORED")?;
+            }
+        } else if col_info.hidden == 1 {
+            // This only comes up in virtual tables
+            write!(output, " hidden")?;
+        }
+        writeln!(output)?;
+    }
+    if pk_cols.len() > 1 {
+        writeln!(output, "PRIMARY KEY ({})", fmt_col_names(&pk_cols))?;
+    }
+    for check in table.table_checks()? {
+        writeln!(output, "CHECK ({})", check)?;
+    }
+
+    for fk_info in foreign_keys.multicolumn() {
+        writeln!(
+            output,
+            "FOREIGN KEY ({}) REFERENCES {} ({}){}",
+            fmt_col_names(&fk_info.from),
+            &fk_info.to_table.bright_green(),
+            fmt_col_names(&fk_info.to),
+            fmt_fk_actions(&fk_info),
+        )?;
+    }
+
+    let referenced_by = table.referenced_by()?;
+    if !referenced_by.is_empty() {
+        writeln!(output, "Referenced by:")?;
+        for (from_table, fk_info) in referenced_by {
+            write!(
+                output,
+                "  {} ({})",
+                escape_identifier(&from_table).bright_green(),
+                fmt_col_names(&fk_info.from)
+            )?;
+            if fk_info.to != [""] {
+                write!(output, " → ({})", fmt_col_names(&fk_info.to))?;
+            }
+            writeln!(output, "{}", fmt_fk_actions(&fk_info))?;
+        }
+    }
+
+    if !other_indexes.is_empty() {
+        writeln!(output, "Indexes:")?;
+        for (ix, cols) in other_indexes {
+            write!(output, "  {} ({})", ix.name, fmt_index_cols(&cols))?;
+            if ix.unique {
+                write!(output, " UNIQUE")?;
+            }
+            if let Some(w) = ix.where_clause(conn)? {
+                write!(output, " WHERE {}", w)?;
+            }
+            writeln!(output)?;
+        }
+    }
+
+    display_stats(table, nrows, output)?;
+    display_triggers(table, output)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+/// Describe one view for the schema view.
+/// Views and tables are similar enough for this to work.
+fn write_view_schema(
+    view: &Table,
+    counter: &RowCounter,
+    output: &mut String,
+) -> anyhow::Result<()> {
+    writeln!(
+        output,
+        "{} view ({} rows):",
+        view.escaped_name().bright_green().bold(),
+        counter.count(view)?
+    )?;
+
+    for col_info in view.columns_info()? {
+        writeln!(output, "  {}", col_info.name.cyan())?;
+    }
+
+    // Find the 'AS SELECT' clause for this view
+    let ast = Parser::parse_sql(&SQLiteDialect {}, &view.create_sql()?)?;
+    if let Some(Statement::CreateView(CreateView { query: q, .. })) = ast.first() {
+        writeln!(output, "AS {q}")?;
+    }
+
+    display_triggers(view, output)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+/// Are the planner's statistics for a table far enough from the real row count
+/// to be worth running ANALYZE again? Small tables are ignored.
+fn stats_out_of_date(stat_rows: u64, nrows: u64) -> bool {
+    let (low, high) = (stat_rows.min(nrows), stat_rows.max(nrows));
+    high >= 100 && high > low * 2
+}
+
+/// Show what the query planner knows about a table from sqlite_stat1 & 4
+fn display_stats(table: &Table, nrows: RowCount, output: &mut String) -> anyhow::Result<()> {
+    // ANALYZE doesn't gather statistics for virtual tables
+    if !has_table(&table.conn, &table.schema, "sqlite_stat1")? || table.obj_type()? == "virtual" {
+        return Ok(());
+    }
+    let Some(stat_rows) = table.stat_rows()? else {
+        // Without a count (e.g. --no-count), we can't tell if the table is empty
+        if nrows.value().is_some_and(|n| n > 0) {
+            writeln!(output, "{} Statistics missing: run ANALYZE", "⚠".yellow())?;
+        }
+        return Ok(());
+    };
+    // An estimated count may come from the statistics, so only compare exact counts
+    if matches!(nrows, RowCount::Exact(n) if stats_out_of_date(stat_rows, n)) {
+        writeln!(
+            output,
+            "{} Statistics out of date: planner expects ~{stat_rows} rows",
+            "⚠".yellow()
+        )?;
+    }
+    let mut lines = Vec::new();
+    for ix in table.indexes_info()? {
+        let Some(stats) = ix.stats(&table.conn)? else {
+            continue;
+        };
+        let mut line = format!("  {}: ~{} entries", ix.name, stats.entries);
+        if !stats.per_key.is_empty() {
+            let per_key: Vec<String> = stats.per_key.iter().map(|n| n.to_string()).collect();
+            write!(line, ", rows per key prefix: {}", per_key.join(", "))?;
+        }
+        if let Some(samples) = stats.samples {
+            write!(line, ", {samples} samples")?;
+        }
+        lines.push(line);
+    }
+    if !lines.is_empty() {
+        writeln!(output, "Index statistics:")?;
+        for line in lines {
+            writeln!(output, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+fn display_triggers(table: &Table, output: &mut String) -> anyhow::Result<()> {
+    let triggers = table.triggers_info()?;
+    if triggers.is_empty() {
+        return Ok(());
+    }
+    writeln!(output, "Triggers:")?;
+    for trigger in triggers {
+        write!(output, "  {}", trigger.name.bright_magenta())?;
+        if trigger.schema != "main" {
+            write!(output, " [in {}]", trigger.schema.bold())?;
+        }
+        if let Some((header, body)) = trigger.parts() {
+            writeln!(output, " {header}")?;
+            for stmt in body {
+                writeln!(output, "    {stmt};")?;
+            }
+        } else {
             writeln!(output, " (could not parse CREATE TRIGGER statement)")?;
         }
     }
     Ok(())
 }
 
+fn open_db(path: &Path) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+}
+
+/// Does this file start with the SQLite database header?
+fn is_sqlite_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    path.is_file()
+        && std::fs::File::open(path)
+            .and_then(|mut f| f.read_exact(&mut header))
+            .is_ok()
+        && &header == b"SQLite format 3\0"
+}
+
+/// Replace directories with the SQLite files directly inside them
+fn find_db_files(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut res = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            for entry in std::fs::read_dir(path)? {
+                let entry_path = entry?.path();
+                if is_sqlite_file(&entry_path) {
+                    found.push(entry_path);
+                }
+            }
+            found.sort();
+            res.extend(found);
+        } else {
+            res.push(path.clone());
+        }
+    }
+    Ok(res)
+}
+
+/// Get the summary cells for one database file: size, page size, tables,
+/// rows, journal mode & user_version
+fn db_summary(path: &Path, counter: &RowCounter) -> anyhow::Result<Vec<String>> {
+    let size = std::fs::metadata(path)?.len();
+    let conn = Rc::new(open_db(path)?);
+    let page_size: u64 = conn.query_row("PRAGMA page_size", [], |r| r.get(0))?;
+    let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |r| r.get(0))?;
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    let tables = get_tables(&conn, &false)?;
+    let nrows = counter.total(&tables);
+    Ok(vec![
+        fmt_n_bytes(size as usize),
+        page_size.to_string(),
+        tables.len().to_string(),
+        nrows.to_string(),
+        journal_mode,
+        user_version.to_string(),
+    ])
+}
+
+/// Show one line of summary information for each database file
+/// Main implementation for `sqlite-glance a.db b.db` & `sqlite-glance dir/`
+/// `deadline` is when to stop counting rows, across all the files.
+fn summarise_dbs(
+    paths: &[PathBuf],
+    count_mode: CountMode,
+    deadline: Instant,
+) -> anyhow::Result<()> {
+    let mut table = comfy_table::Table::new();
+    table.load_preset(UTF8_FULL).set_header([
+        "File",
+        "Size",
+        "Page size",
+        "Tables",
+        "Rows",
+        "Journal",
+        "user_version",
+    ]);
+    for path in paths {
+        let mut row = vec![path.display().to_string()];
+        // One unreadable file shouldn't stop us summarising the others
+        match db_summary(path, &RowCounter::new(count_mode, deadline)) {
+            Ok(cells) => row.extend(cells),
+            Err(e) => row.push(format!("Error: {}", e)),
+        }
+        table.add_row(row);
+    }
+    for column in table.column_iter_mut().skip(1).take(4) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+
+    let output = format!("{}\n{}", table, plural(paths.len(), "database file"));
+    let tbl_width = output.lines().next().map_or(0, |l| l.chars().count());
+    print_output(&output, Some(tbl_width))
+}
+
+/// Parse a number of seconds, like `--count-timeout 2.5`.
+/// Longer than a year is treated as a year, so adding it to the current time
+/// can't overflow.
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    const YEAR: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+    s.parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .map(|d| d.min(YEAR))
+        .ok_or_else(|| format!("not a number of seconds: {}", s))
+}
+
+/// Parse `--attach alias=path.db`
+fn parse_attach(s: &str) -> Result<(String, PathBuf), String> {
+    match s.split_once('=') {
+        Some((alias, path)) if !alias.is_empty() && !path.is_empty() => {
+            Ok((alias.to_string(), PathBuf::from(path)))
+        }
+        _ => Err("expected ALIAS=PATH".to_string()),
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let matches = Command::new("sqlite-glance")
         .version(env!("CARGO_PKG_VERSION"))
         .arg(
             Arg::new("path")
                 .required(true)
-                .help("SQLite file to inspect")
+                .help("SQLite file to inspect, or a directory to summarise SQLite files in")
                 .value_parser(value_parser!(PathBuf)),
         )
         .arg(
-            Arg::new("table")
-                .required(false)
-                .help("Table or view to inspect"),
+            Arg::new("table").required(false).help(
+                "Table or view to inspect (or an index or trigger), optionally as schema.name",
+            ),
+        )
+        .arg(
+            Arg::new("more")
+                .value_name("PATH")
+                .num_args(1..)
+                .value_parser(value_parser!(PathBuf))
+                .help("More SQLite files, to show a summary of each"),
+        )
+        .arg(
+            Arg::new("summary")
+                .long("summary")
+                .action(ArgAction::SetTrue)
+                .help("Show a one-line summary of each SQLite file given"),
+        )
+        .arg(
+            Arg::new("attach")
+                .long("attach")
+                .value_name("ALIAS=PATH")
+                .action(ArgAction::Append)
+                .value_parser(parse_attach)
+                .help("Attach another SQLite file as schema ALIAS (may be repeated)"),
         )
         .arg(
             Arg::new("hidden")
@@ -414,12 +1779,103 @@ fn main() -> anyhow::Result<()> {
                     "Show shadow tables, SQLite system tables & hidden columns in virtual tables",
                 ),
         )
+        .arg(
+            Arg::new("list")
+                .short('l')
+                .long("list")
+                .action(ArgAction::SetTrue)
+                .help("List tables & views with one line each, instead of the full schema"),
+        )
+        .arg(
+            Arg::new("tables")
+                .long("tables")
+                .value_name("PATTERN")
+                .action(ArgAction::Append)
+                .help("Only show tables & views matching a glob pattern, e.g. 'order*'"),
+        )
+        .arg(
+            Arg::new("exclude-tables")
+                .long("exclude-tables")
+                .value_name("PATTERN")
+                .action(ArgAction::Append)
+                .help("Don't show tables & views matching a glob pattern"),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .value_name("quick|full")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("full")
+                .value_parser(["quick", "full"])
+                .help("Check for corruption & foreign key violations, without changing the file"),
+        )
+        .arg(
+            Arg::new("lint")
+                .long("lint")
+                .action(ArgAction::SetTrue)
+                .help("Warn about common problems in table definitions, exiting with status 1 if any are found"),
+        )
+        .arg(
+            Arg::new("info")
+                .long("info")
+                .action(ArgAction::SetTrue)
+                .help("Show database settings from the file header & pragmas, instead of the schema"),
+        )
+        .arg(
+            Arg::new("sizes")
+                .long("sizes")
+                .action(ArgAction::SetTrue)
+                .help("Show the space used by each table & index, instead of the schema"),
+        )
+        // Modes which show something other than the schema
+        .group(ArgGroup::new("mode").args(["list", "check", "lint", "info", "sizes"]))
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("ORDER")
+                .value_parser(["name", "rows", "size", "fk"])
+                .help("Sort tables by name, rows or size (biggest first), or so foreign keys refer to earlier tables (fk)"),
+        )
+        .arg(
+            Arg::new("group")
+                .long("group")
+                .action(ArgAction::SetTrue)
+                .help("Group tables by name prefix, up to the first '_'"),
+        )
+        .arg(
+            Arg::new("estimate-counts")
+                .long("estimate-counts")
+                .action(ArgAction::SetTrue)
+                .help("Estimate the rows in each table (shown as ~N) instead of counting them"),
+        )
+        .arg(
+            Arg::new("no-count")
+                .long("no-count")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("estimate-counts")
+                .help("Don't count or estimate the rows in each table"),
+        )
+        .arg(
+            Arg::new("count-timeout")
+                .long("count-timeout")
+                .value_name("SECONDS")
+                .default_value("5")
+                .value_parser(parse_seconds)
+                .help("Stop counting rows after this long, and estimate the rest"),
+        )
         .arg(
             Arg::new("where")
                 .short('w')
                 .long("where")
                 .help("WHERE clause to select rows in table view"),
         )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .help("Show the query plan SQLite uses to select rows in table view"),
+        )
         .arg(
             Arg::new("limit")
                 .short('n')
@@ -429,29 +1885,156 @@ fn main() -> anyhow::Result<()> {
                 .value_parser(value_parser!(u32))
                 .help("Maximum number of rows to show in table view"),
         )
+        .arg(
+            Arg::new("max-width")
+                .long("max-width")
+                .value_name("N|auto")
+                .default_value("auto")
+                .value_parser(parse_max_width)
+                .help("Maximum characters per cell in table view (0 for no limit)"),
+        )
         .get_matches();
 
     yansi::whenever(Condition::TTY_AND_COLOR);
 
     let path = matches.get_one::<PathBuf>("path").unwrap();
+    let table_arg = matches.get_one::<String>("table");
+    let count_mode = if matches.get_flag("no-count") {
+        CountMode::None
+    } else if matches.get_flag("estimate-counts") {
+        CountMode::Estimate
+    } else {
+        CountMode::Exact
+    };
+    let count_deadline = Instant::now() + *matches.get_one::<Duration>("count-timeout").unwrap();
+    // Several files, or a directory - show a summary of each database.
+    // A second argument is a table name unless it looks like a path, so a
+    // file in the current directory doesn't hide a table with the same name.
+    if path.is_dir()
+        || matches.get_flag("summary")
+        || matches.contains_id("more")
+        || table_arg.is_some_and(|t| t.contains(['/', std::path::MAIN_SEPARATOR]))
+    {
+        let mut paths = vec![path.clone()];
+        paths.extend(table_arg.map(PathBuf::from));
+        if let Some(more) = matches.get_many::<PathBuf>("more") {
+            paths.extend(more.cloned());
+        }
+        return summarise_dbs(&find_db_files(&paths)?, count_mode, count_deadline);
+    }
+
     let filename = PathBuf::from(path.file_name().unwrap());
-    let conn = Rc::new(Connection::open_with_flags(
-        path,
-        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
-    )?);
+    let conn = Rc::new(open_db(path)?);
+    // Attached databases are opened with the same (read-only) flags
+    if let Some(attachments) = matches.get_many::<(String, PathBuf)>("attach") {
+        for (alias, path) in attachments {
+            let path = path
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Non-UTF-8 path: {}", path.display()))?;
+            conn.execute("ATTACH DATABASE ? AS ?", [path, alias])?;
+        }
+    }
+    // Our own functions first, so they aren't replaced by stand-ins
+    functions::register_functions(&conn)?;
+    fallbacks::register_fallbacks(&conn, !matches.contains_id("check"))?;
 
-    if let Some(table_name) = matches.get_one::<String>("table") {
+    let patterns = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .map_or_else(Vec::new, |v| v.cloned().collect())
+    };
+    let filter = TableFilter {
+        include: patterns("tables"),
+        exclude: patterns("exclude-tables"),
+    };
+
+    if let Some(name_arg) = table_arg {
+        let limit = matches.get_one::<u32>("limit").unwrap();
+        let max_width = *matches.get_one::<MaxWidth>("max-width").unwrap();
+        let where_cl = matches.get_one::<String>("where").map(|x| x.as_str());
+        let explain = matches.get_flag("explain");
+        // Names are matched case-insensitively, like in SQLite
+        let Some((schema, name)) = find_name(&conn, name_arg)? else {
+            // A glob pattern shows data from each matching table & view
+            if is_glob_pattern(name_arg) {
+                let filter = TableFilter {
+                    include: vec![name_arg.clone()],
+                    exclude: filter.exclude,
+                };
+                let mut tables = filter.apply(get_tables(&conn, &false)?);
+                tables.extend(filter.apply(get_views(&conn)?));
+                if tables.is_empty() {
+                    anyhow::bail!("No tables or views match: {}", name_arg);
+                }
+                return inspect_tables(&tables, &filename, where_cl, limit, max_width, explain);
+            }
+            // Not a name in this database, but another database file
+            if is_sqlite_file(Path::new(name_arg)) {
+                let paths = [path.clone(), PathBuf::from(name_arg)];
+                return summarise_dbs(&paths, count_mode, count_deadline);
+            }
+            let mut msg = format!("No such table, view, index or trigger: {}", name_arg);
+            if Path::new(name_arg).exists() {
+                write!(msg, "\nTo summarise several databases, use --summary")?;
+            }
+            let suggestions = suggest_names(&conn, name_arg)?;
+            if !suggestions.is_empty() {
+                write!(msg, "\nDid you mean: {}?", suggestions.join(", "))?;
+            }
+            anyhow::bail!(msg);
+        };
         // Table/view name specified - show data
-        let table = Table::new(table_name, Rc::clone(&conn));
-        if !table.in_db()? {
-            anyhow::bail!("No such table: {}", table_name);
+        let table = Table::new(&schema, &name, Rc::clone(&conn));
+        if table.in_db()? {
+            return inspect_tables(&[table], &filename, where_cl, limit, max_width, explain);
+        }
+        // Indexes & triggers can also be specified by name
+        match get_object_type(&conn, &schema, &name)?.as_deref() {
+            Some("index") => {
+                let Some((db_table, ix)) = find_index(&conn, &schema, &name)? else {
+                    anyhow::bail!("No such index: {}", name);
+                };
+                inspect_index(
+                    &db_table, &ix, &filename, where_cl, limit, max_width, explain,
+                )
+            }
+            Some("trigger") => {
+                if where_cl.is_some() || explain {
+                    anyhow::bail!("--where and --explain can't be used with a trigger");
+                }
+                inspect_trigger(&conn, &schema, &name, &filename)
+            }
+            _ => anyhow::bail!("No such table, view, index or trigger: {}", name),
         }
-        let where_cl = matches.get_one::<String>("where").map(|x| x.as_str());
-        let limit = matches.get_one::<u32>("limit").unwrap();
-        inspect_table(table, &filename, where_cl, limit)
     } else {
         // No table specified - show DB schema
+        let order = TableOrder {
+            sort_by: matches.get_one::<String>("sort").cloned(),
+            group: matches.get_flag("group"),
+        };
         let inc_hidden = matches.get_one::<bool>("hidden").unwrap();
-        inspect_schema(conn, &filename, &inc_hidden)
+        let counter = RowCounter::new(count_mode, count_deadline);
+        if let Some(level) = matches.get_one::<String>("check") {
+            let limit = matches.get_one::<u32>("limit").unwrap();
+            let max_width = *matches.get_one::<MaxWidth>("max-width").unwrap();
+            let ok = inspect_check(conn, &filename, level == "quick", &filter, limit, max_width)?;
+            if !ok {
+                process::exit(1);
+            }
+            Ok(())
+        } else if matches.get_flag("lint") {
+            if !inspect_lint(conn, &filename, &filter)? {
+                process::exit(1);
+            }
+            Ok(())
+        } else if matches.get_flag("info") {
+            inspect_info(conn, path, &filename)
+        } else if matches.get_flag("sizes") {
+            inspect_sizes(conn, &filename, &filter)
+        } else if matches.get_flag("list") {
+            list_schema(conn, &filename, inc_hidden, &filter, &order, &counter)
+        } else {
+            inspect_schema(conn, &filename, inc_hidden, &filter, &order, &counter)
+        }
     }
 }
@@ -1,11 +1,22 @@
+use std::collections::HashSet;
+use std::fmt;
 use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Instant;
 
-use rusqlite::{Connection, Result, Row, Rows};
-use sqlparser::ast::{ColumnDef, ColumnOption, Statement};
+use rusqlite::types::FromSql;
+use rusqlite::{Connection, ErrorCode, OptionalExtension, Result, Row, Rows};
+use sqlparser::ast::{
+    ColumnDef, ColumnOption, CreateIndex, Expr, ForeignKeyConstraint,
+    IndexColumn as IndexColumnAst, OrderByExpr, Spanned, Statement, TableConstraint,
+};
 use sqlparser::dialect::SQLiteDialect;
+use sqlparser::keywords::Keyword;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Location, Token, Tokenizer};
 
-mod keywords;
+pub mod keywords;
 mod tests;
 
 #[derive(Debug)]
@@ -15,6 +26,8 @@ pub struct ColumnInfo {
     pub notnull: bool,
     pub pk: u8,
     pub hidden: u8,
+    /// The DEFAULT expression, as written in the CREATE TABLE statement
+    pub default: Option<String>,
 }
 
 impl ColumnInfo {
@@ -25,21 +38,114 @@ impl ColumnInfo {
             notnull: row.get("notnull")?,
             pk: row.get("pk")?,
             hidden: row.get("hidden")?,
+            default: row.get("dflt_value")?,
         })
     }
+
+    /// Format the default value as for a DEFAULT clause.
+    /// SQLite drops the parentheses needed around expressions, so add them back.
+    pub fn default_sql(&self) -> Option<String> {
+        let d = self.default.as_ref()?;
+        let upper = d.to_ascii_uppercase();
+        let is_literal = d.starts_with('\'')
+            || ((upper.starts_with("X'")) && d.ends_with('\''))
+            || d.trim_start_matches(['-', '+']).parse::<f64>().is_ok()
+            || [
+                "NULL",
+                "TRUE",
+                "FALSE",
+                "CURRENT_TIME",
+                "CURRENT_DATE",
+                "CURRENT_TIMESTAMP",
+            ]
+            .contains(&upper.as_str());
+        if is_literal {
+            Some(d.clone())
+        } else {
+            Some(format!("({})", d))
+        }
+    }
+}
+
+/// Column constraints which are only found by parsing CREATE TABLE
+#[derive(Debug, Default, PartialEq)]
+pub struct ColumnConstraints {
+    pub collation: Option<String>,
+    pub checks: Vec<String>,
+    pub autoincrement: bool,
+    /// ON CONFLICT clauses for NOT NULL, PRIMARY KEY & UNIQUE constraints
+    pub not_null_conflict: Option<String>,
+    pub pk_conflict: Option<String>,
+    pub unique_conflict: Option<String>,
+}
+
+impl ColumnConstraints {
+    /// `sql` is the CREATE TABLE statement the column definition is from
+    fn from_col_def(coldef: &ColumnDef, sql: &str) -> ColumnConstraints {
+        let mut res = ColumnConstraints::default();
+        // ON CONFLICT is parsed as a separate option after the constraint it modifies
+        let mut prev: Option<&ColumnOption> = None;
+        for o in &coldef.options {
+            match &o.option {
+                ColumnOption::Collation(name) => res.collation = Some(name.to_string()),
+                ColumnOption::Check(c) => res.checks.push(check_text(sql, &c.expr)),
+                ColumnOption::DialectSpecific(tokens)
+                    if tokens.iter().any(
+                        |t| matches!(t, Token::Word(w) if w.keyword == Keyword::AUTOINCREMENT),
+                    ) =>
+                {
+                    res.autoincrement = true;
+                }
+                ColumnOption::OnConflict(_) => {
+                    let clause = Some(o.option.to_string());
+                    match prev {
+                        Some(ColumnOption::NotNull) => res.not_null_conflict = clause,
+                        Some(ColumnOption::PrimaryKey(_)) => res.pk_conflict = clause,
+                        Some(ColumnOption::Unique(_)) => res.unique_conflict = clause,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            prev = Some(&o.option);
+        }
+        res
+    }
 }
 
 #[derive(Debug)]
 pub struct IndexInfo {
+    /// Indexes are always in the same schema as their table
+    pub schema: String,
     pub name: String,
     pub unique: bool,
     pub origin: String,
     pub partial: bool,
 }
 
+/// One column or expression which an index is keyed on
+#[derive(Debug, PartialEq)]
+pub struct IndexColumn {
+    /// Column name, or SQL for an expression
+    pub name: String,
+    pub is_expr: bool,
+    pub desc: bool,
+    pub collation: String,
+}
+
+impl IndexColumn {
+    /// Is this a plain column, in ascending order with the default collation?
+    /// `col_collation` is the collation declared on the table column, if any.
+    pub fn is_plain(&self, col_collation: Option<&str>) -> bool {
+        let default_coll = col_collation.unwrap_or("BINARY");
+        !self.is_expr && !self.desc && self.collation.eq_ignore_ascii_case(default_coll)
+    }
+}
+
 impl IndexInfo {
-    fn from_row(row: &Row) -> Result<IndexInfo> {
+    fn from_row(row: &Row, schema: &str) -> Result<IndexInfo> {
         Ok(IndexInfo {
+            schema: schema.to_string(),
             name: row.get("name")?,
             unique: row.get("unique")?,
             origin: row.get("origin")?,
@@ -47,20 +153,144 @@ impl IndexInfo {
         })
     }
 
-    pub fn column_names(&self, conn: &Connection) -> Result<Vec<String>> {
-        let mut stmt =
-            conn.prepare("SELECT cid, name from pragma_index_info(?) ORDER BY seqno ASC")?;
-        let mut rows = stmt.query([&self.name])?;
+    /// Get the CREATE INDEX statement, if there is one.
+    /// Indexes made automatically for UNIQUE & PRIMARY KEY constraints have none.
+    pub fn create_sql(&self, conn: &Connection) -> Result<Option<String>> {
+        // The primary key index of a WITHOUT ROWID table isn't in sqlite_schema
+        let sql: Option<Option<String>> = conn
+            .query_row(
+                &format!(
+                    "SELECT sql FROM {}.sqlite_schema WHERE type='index' AND name=? COLLATE NOCASE",
+                    escape_identifier(&self.schema)
+                ),
+                [&self.name],
+                |r| r.get(0),
+            )
+            .optional()?;
+        Ok(sql.flatten())
+    }
+
+    /// Parse the CREATE INDEX statement, if there is one.
+    fn create_ast(&self, conn: &Connection) -> Result<Option<CreateIndex>> {
+        let sql = self.create_sql(conn)?;
+        if let Some(Ok(ast)) = sql.map(|s| Parser::parse_sql(&SQLiteDialect {}, &s)) {
+            if let Some(Statement::CreateIndex(ci)) = ast.into_iter().next() {
+                return Ok(Some(ci));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get query planner statistics for this index, if ANALYZE has stored them
+    pub fn stats(&self, conn: &Connection) -> Result<Option<IndexStats>> {
+        if !has_table(conn, &self.schema, "sqlite_stat1")? {
+            return Ok(None);
+        }
+        let schema = escape_identifier(&self.schema);
+        let stat: Option<String> = conn
+            .query_row(
+                &format!("SELECT stat FROM {}.sqlite_stat1 WHERE idx = ?", schema),
+                [&self.name],
+                |r| r.get(0),
+            )
+            .optional()?;
+        let numbers = stat.as_deref().map(parse_stat1).unwrap_or_default();
+        let [entries, per_key @ ..] = numbers.as_slice() else {
+            return Ok(None);
+        };
+        let samples = if has_table(conn, &self.schema, "sqlite_stat4")? {
+            Some(conn.query_row(
+                &format!("SELECT count(*) FROM {}.sqlite_stat4 WHERE idx = ?", schema),
+                [&self.name],
+                |r| r.get(0),
+            )?)
+        } else {
+            None
+        };
+        Ok(Some(IndexStats {
+            entries: *entries,
+            per_key: per_key.to_vec(),
+            samples,
+        }))
+    }
+
+    /// Get the key columns & expressions of this index, in order
+    pub fn columns(&self, conn: &Connection) -> Result<Vec<IndexColumn>> {
+        let ast_cols = self.create_ast(conn)?.map(|ci| ci.columns);
+        let mut stmt = conn.prepare(
+            "SELECT seqno, cid, name, desc, coll FROM pragma_index_xinfo(?, ?) \
+             WHERE key=1 ORDER BY seqno ASC",
+        )?;
+        let mut rows = stmt.query([&self.name, &self.schema])?;
         let mut res = Vec::new();
         while let Some(row) = rows.next()? {
-            res.push(match row.get("cid")? {
+            let cid: i64 = row.get("cid")?;
+            let name = match cid {
                 -1 => "<rowid>".to_string(),
-                -2 => "<expression>".to_string(),
+                -2 => {
+                    let seqno: usize = row.get("seqno")?;
+                    match ast_cols.as_ref().and_then(|cols| cols.get(seqno)) {
+                        // COLLATE is shown separately, from the pragma
+                        Some(IndexColumnAst {
+                            column:
+                                OrderByExpr {
+                                    expr: Expr::Collate { expr, .. },
+                                    ..
+                                },
+                            ..
+                        }) => expr.to_string(),
+                        Some(c) => c.column.expr.to_string(),
+                        None => "<expression>".to_string(),
+                    }
+                }
                 _ => row.get("name")?,
+            };
+            res.push(IndexColumn {
+                name,
+                is_expr: cid == -2,
+                desc: row.get("desc")?,
+                collation: row.get("coll")?,
             })
         }
         Ok(res)
     }
+
+    pub fn column_names(&self, conn: &Connection) -> Result<Vec<String>> {
+        Ok(self.columns(conn)?.into_iter().map(|c| c.name).collect())
+    }
+
+    /// Get the WHERE expression of a partial index
+    pub fn where_clause(&self, conn: &Connection) -> Result<Option<String>> {
+        if !self.partial {
+            return Ok(None);
+        }
+        Ok(Some(
+            match self.create_ast(conn)?.and_then(|ci| ci.predicate) {
+                Some(e) => e.to_string(),
+                None => "<could not get WHERE expression>".to_string(),
+            },
+        ))
+    }
+}
+
+/// Query planner statistics for an index, gathered by ANALYZE
+#[derive(Debug, PartialEq)]
+pub struct IndexStats {
+    /// Approximate number of entries in the index
+    pub entries: u64,
+    /// Average number of entries with the same values in the first 1, 2, ...
+    /// columns of the index
+    pub per_key: Vec<u64>,
+    /// Number of samples in sqlite_stat4, if that table exists
+    pub samples: Option<u64>,
+}
+
+/// Parse the numbers at the start of the `stat` column in sqlite_stat1.
+/// Other things may follow them, like 'unordered' or 'sz=12'.
+fn parse_stat1(stat: &str) -> Vec<u64> {
+    stat.split_whitespace()
+        .map_while(|s| s.parse().ok())
+        .collect()
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -70,17 +300,24 @@ pub struct ForeignKeyInfo {
     pub to: Vec<String>,
     pub on_update: String,
     pub on_delete: String,
+    // SQLite's pragma doesn't report these, so they are found by parsing SQL
+    /// e.g. MATCH FULL
+    pub match_kind: Option<String>,
+    /// e.g. DEFERRABLE INITIALLY DEFERRED
+    pub deferrable: Option<String>,
 }
 
 impl ForeignKeyInfo {
     fn new() -> ForeignKeyInfo {
-        return ForeignKeyInfo {
+        ForeignKeyInfo {
             to_table: "".to_string(),
             from: Vec::new(),
             to: Vec::new(),
             on_update: "NO ACTION".to_string(),
             on_delete: "NO ACTION".to_string(),
-        };
+            match_kind: None,
+            deferrable: None,
+        }
     }
 }
 
@@ -107,7 +344,7 @@ impl ForeignKeys {
             current.on_update = row.get("on_update")?;
             current.on_delete = row.get("on_delete")?;
         }
-        if current.from.len() > 0 {
+        if !current.from.is_empty() {
             l.push(current);
         }
         Ok(ForeignKeys { list: l })
@@ -115,7 +352,7 @@ impl ForeignKeys {
 
     pub fn for_name(&self, name: &str) -> Option<ForeignKeyInfo> {
         for fk in &self.list {
-            if fk.from.len() == 1 && &fk.from[0] == name {
+            if fk.from.len() == 1 && fk.from[0] == name {
                 return Some(fk.clone());
             }
         }
@@ -133,45 +370,265 @@ impl ForeignKeys {
     }
 }
 
+pub struct TriggerInfo {
+    /// The schema the trigger is stored in: 'main', 'temp' or an attached database
+    pub schema: String,
+    pub name: String,
+    pub sql: String,
+}
+
+impl TriggerInfo {
+    /// Split the CREATE TRIGGER statement into when it fires, like
+    /// `BEFORE UPDATE OF a WHEN new.a > 0`, and the statements in its body.
+    /// These are shown as written, but with whitespace tidied & no comments.
+    pub fn parts(&self) -> Option<(String, Vec<String>)> {
+        let tokens = tokens_with_text(&self.sql)?;
+        let is_kw = |i: usize, kw: Keyword| matches!(&tokens[i].0, Token::Word(w) if w.keyword == kw && w.quote_style.is_none());
+        let find_kw = |from: usize, kw: Keyword| (from..tokens.len()).find(|&i| is_kw(i, kw));
+
+        // Skip CREATE [TEMP] TRIGGER [IF NOT EXISTS] [schema.]name
+        let mut start = find_kw(0, Keyword::TRIGGER)? + 1;
+        if is_kw(start, Keyword::IF) {
+            start += 3;
+        }
+        start += if tokens.get(start + 1)?.0 == Token::Period {
+            3
+        } else {
+            1
+        };
+        let begin = find_kw(start, Keyword::BEGIN)?;
+        let end = (begin..tokens.len())
+            .rev()
+            .find(|&i| is_kw(i, Keyword::END))?;
+
+        // Leave out ON [schema.]table, as triggers are shown with their table
+        let mut header: Vec<_> = tokens[start..begin].to_vec();
+        if let Some(on) = (0..header.len()).find(|&i| is_kw(start + i, Keyword::ON)) {
+            let len = if header.get(on + 2).map(|t| &t.0) == Some(&Token::Period) {
+                4
+            } else {
+                2
+            };
+            header.drain(on..(on + len).min(header.len()));
+        }
+        let body = tokens[begin + 1..end]
+            .split(|t| t.0 == Token::SemiColon)
+            .filter(|stmt| !stmt.is_empty())
+            .map(join_tokens)
+            .collect();
+        Some((join_tokens(&header), body))
+    }
+}
+
+/// Tokenize SQL, keeping the text each token was written as. Whitespace &
+/// comments are left out; the bool says if there was any before the token.
+fn tokens_with_text(sql: &str) -> Option<Vec<(Token, &str, bool)>> {
+    let tokens = Tokenizer::new(&SQLiteDialect {}, sql)
+        .tokenize_with_location()
+        .ok()?;
+    let offset = |loc: Location| location_offset(sql, loc).unwrap_or(sql.len());
+    let mut res = Vec::new();
+    let mut space_before = false;
+    for (i, t) in tokens.iter().enumerate() {
+        if let Token::Whitespace(_) = t.token {
+            space_before = true;
+            continue;
+        }
+        let end = tokens
+            .get(i + 1)
+            .map_or(sql.len(), |next| offset(next.span.start));
+        res.push((
+            t.token.clone(),
+            &sql[offset(t.span.start)..end],
+            space_before,
+        ));
+        space_before = false;
+    }
+    Some(res)
+}
+
+/// Convert a location in SQL to a byte offset. Locations count lines &
+/// characters from 1; line 0 means the location isn't known.
+fn location_offset(sql: &str, loc: Location) -> Option<usize> {
+    let line_start = if loc.line == 1 {
+        0
+    } else {
+        sql.match_indices('\n')
+            .nth(loc.line.checked_sub(2)? as usize)?
+            .0
+            + 1
+    };
+    let line = &sql[line_start..];
+    let col = line
+        .char_indices()
+        .nth(loc.column.checked_sub(1)? as usize)
+        .map_or(line.len(), |(i, _)| i);
+    Some(line_start + col)
+}
+
+/// Get the expression of a CHECK constraint as it's written in `sql`, the
+/// CREATE TABLE statement it was parsed from. sqlparser's locations don't
+/// always include the end of an expression, so this looks for the
+/// parentheses around it.
+fn check_text(sql: &str, expr: &Expr) -> String {
+    let find = || -> Option<String> {
+        let expr_start = location_offset(sql, expr.span().start)?;
+        let tokens: Vec<_> = Tokenizer::new(&SQLiteDialect {}, sql)
+            .tokenize_with_location()
+            .ok()?
+            .into_iter()
+            .filter(|t| !matches!(t.token, Token::Whitespace(_)))
+            .collect();
+        let offset = |i: usize| location_offset(sql, tokens.get(i)?.span.start);
+        // The '(' after the last CHECK before the expression
+        let open = (0..tokens.len())
+            .take_while(|&i| offset(i).is_some_and(|o| o < expr_start))
+            .filter(|&i| matches!(&tokens[i].token, Token::Word(w) if w.keyword == Keyword::CHECK))
+            .last()?
+            + 1;
+        if tokens.get(open)?.token != Token::LParen {
+            return None;
+        }
+        let mut depth = 0;
+        for i in open + 1..tokens.len() {
+            match tokens[i].token {
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 0 => {
+                    return Some(sql[offset(open + 1)?..offset(i)?].trim_end().to_string());
+                }
+                Token::RParen => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    };
+    find().unwrap_or_else(|| expr.to_string())
+}
+
+/// Join tokens into SQL, with a single space wherever there was whitespace
+fn join_tokens(tokens: &[(Token, &str, bool)]) -> String {
+    let mut res = String::new();
+    for (_, text, space_before) in tokens {
+        if *space_before && !res.is_empty() {
+            res.push(' ');
+        }
+        res.push_str(text);
+    }
+    res
+}
+
+/// The number of rows in a table, which may only be an estimate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowCount {
+    Exact(u64),
+    Approx(u64),
+    Unknown,
+}
+
+impl RowCount {
+    pub fn value(self) -> Option<u64> {
+        match self {
+            RowCount::Exact(n) | RowCount::Approx(n) => Some(n),
+            RowCount::Unknown => None,
+        }
+    }
+}
+
+impl fmt::Display for RowCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowCount::Exact(n) => write!(f, "{}", n),
+            RowCount::Approx(n) => write!(f, "~{}", n),
+            RowCount::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+/// Run a query, interrupting it if it's still going at the deadline.
+/// Returns None if it was interrupted.
+fn until_deadline<T>(
+    conn: &Connection,
+    deadline: Instant,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<Option<T>> {
+    if Instant::now() >= deadline {
+        return Ok(None);
+    }
+    // Interrupting when no statement is running does nothing, so the timer
+    // doesn't need to know exactly when the query finishes.
+    let interrupt = conn.get_interrupt_handle();
+    let (done, wait) = mpsc::channel::<()>();
+    let timer = thread::spawn(move || {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if let Err(RecvTimeoutError::Timeout) = wait.recv_timeout(timeout) {
+            interrupt.interrupt();
+        }
+    });
+    let res = f();
+    drop(done);
+    let _ = timer.join();
+    match res {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if e.sqlite_error_code() == Some(ErrorCode::OperationInterrupted) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 pub struct Table {
+    /// 'main', 'temp' or the name of an attached database
+    pub schema: String,
     pub name: String,
     pub conn: Rc<Connection>,
 }
 
 impl Table {
-    pub fn new(name: &str, conn: Rc<Connection>) -> Table {
+    pub fn new(schema: &str, name: &str, conn: Rc<Connection>) -> Table {
         Table {
+            schema: schema.to_string(),
             name: name.to_string(),
-            conn: conn,
+            conn,
         }
     }
 
     /// Does a table/view with this name exist in the database?
     pub fn in_db(&self) -> Result<bool> {
         let count: usize = self.conn.query_row(
-            "SELECT count(*) FROM pragma_table_list WHERE name=?",
-            [&self.name],
+            "SELECT count(*) FROM pragma_table_list \
+             WHERE schema=? COLLATE NOCASE AND name=? COLLATE NOCASE",
+            [&self.schema, &self.name],
             |r| r.get(0),
         )?;
         Ok(count > 0)
     }
 
+    /// Get a column from pragma_table_list for this table
+    fn table_list_info<T: FromSql>(&self, column: &str) -> Result<T> {
+        self.conn.query_row(
+            &format!(
+                "SELECT {} FROM pragma_table_list \
+                 WHERE schema=? COLLATE NOCASE AND name=? COLLATE NOCASE",
+                column
+            ),
+            [&self.schema, &self.name],
+            |r| r.get(0),
+        )
+    }
+
     /// 'table' or 'view'
     pub fn obj_type(&self) -> Result<String> {
-        Ok(self.conn.query_row(
-            "SELECT type FROM pragma_table_list WHERE name=?",
-            [&self.name],
-            |r| r.get(0),
-        )?)
+        self.table_list_info("type")
     }
 
     /// Get the CREATE TABLE / CREATE VIEW statement for this object
     pub fn create_sql(&self) -> Result<String> {
-        Ok(self.conn.query_row(
-            "SELECT sql from sqlite_schema WHERE name=?",
+        self.conn.query_row(
+            &format!(
+                "SELECT sql from {}.sqlite_schema WHERE name=? COLLATE NOCASE",
+                escape_identifier(&self.schema)
+            ),
             [&self.name],
             |r| r.get(0),
-        )?)
+        )
     }
 
     /// Get the name of the module a virtual table is using
@@ -188,35 +645,25 @@ impl Table {
     }
 
     pub fn is_shadow(&self) -> Result<bool> {
-        let ttype: String = self.conn.query_row(
-            "SELECT type FROM pragma_table_list WHERE name=?",
-            [&self.name],
-            |r| r.get(0),
-        )?;
+        let ttype: String = self.table_list_info("type")?;
         Ok(ttype == "shadow")
     }
 
     pub fn is_strict(&self) -> Result<bool> {
-        let i: i64 = self.conn.query_row(
-            "SELECT strict FROM pragma_table_list WHERE name=?",
-            [&self.name],
-            |r| r.get(0),
-        )?;
+        let i: i64 = self.table_list_info("strict")?;
         Ok(i == 1)
     }
 
     pub fn is_without_row_id(&self) -> Result<bool> {
-        let i: i64 = self.conn.query_row(
-            "SELECT wr FROM pragma_table_list WHERE name=?",
-            [&self.name],
-            |r| r.get(0),
-        )?;
+        let i: i64 = self.table_list_info("wr")?;
         Ok(i == 1)
     }
 
     pub fn columns_info(&self) -> Result<Vec<ColumnInfo>> {
-        let mut stmt = self.conn.prepare("SELECT * from pragma_table_xinfo(?)")?;
-        let rows = stmt.query_map([&self.name], |row| ColumnInfo::from_row(row))?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * from pragma_table_xinfo(?, ?)")?;
+        let rows = stmt.query_map([&self.name, &self.schema], ColumnInfo::from_row)?;
         let mut res = Vec::new();
         for info_result in rows {
             res.push(info_result?);
@@ -226,8 +673,10 @@ impl Table {
 
     /// Get information about indexes on this table
     pub fn indexes_info(&self) -> Result<Vec<IndexInfo>> {
-        let mut stmt = self.conn.prepare("SELECT * FROM pragma_index_list(?)")?;
-        let rows = stmt.query_map([&self.name], |row| IndexInfo::from_row(row))?;
+        let mut stmt = self.conn.prepare("SELECT * FROM pragma_index_list(?, ?)")?;
+        let rows = stmt.query_map([&self.name, &self.schema], |row| {
+            IndexInfo::from_row(row, &self.schema)
+        })?;
         let mut res = Vec::new();
         for result in rows {
             res.push(result?);
@@ -238,44 +687,198 @@ impl Table {
     pub fn foreign_key_info(&self) -> Result<ForeignKeys> {
         let mut stmt = self
             .conn
-            .prepare("SELECT * FROM pragma_foreign_key_list(?)")?;
-        let rows = stmt.query([&self.name])?;
-        ForeignKeys::from_rows(rows)
+            .prepare("SELECT * FROM pragma_foreign_key_list(?, ?)")?;
+        let rows = stmt.query([&self.name, &self.schema])?;
+        let mut fks = ForeignKeys::from_rows(rows)?;
+
+        // MATCH & DEFERRABLE aren't available from the pragma, so find them in the SQL
+        if !fks.list.is_empty() {
+            let mut matched = vec![false; fks.list.len()];
+            for (from, fkc) in self.fk_constraints_ast()? {
+                let to_table = fkc.foreign_table.0.last().and_then(|p| p.as_ident());
+                let to: Vec<String> = fkc
+                    .referred_columns
+                    .iter()
+                    .map(|i| i.value.clone())
+                    .collect();
+                // One column may have several foreign keys, to different tables
+                let found = (0..fks.list.len()).find(|&i| {
+                    let fk = &fks.list[i];
+                    !matched[i]
+                        && same_names(&fk.from, &from)
+                        && to_table.is_some_and(|t| t.value.eq_ignore_ascii_case(&fk.to_table))
+                        && (same_names(&fk.to, &to)
+                            || (to.is_empty() && fk.to.iter().all(|c| c.is_empty())))
+                });
+                if let Some(i) = found {
+                    matched[i] = true;
+                    let fk = &mut fks.list[i];
+                    fk.match_kind = fkc.match_kind.map(|m| m.to_string());
+                    fk.deferrable = fkc
+                        .characteristics
+                        .as_ref()
+                        .filter(|c| c.deferrable == Some(true))
+                        .map(|c| c.to_string());
+                }
+            }
+        }
+        Ok(fks)
     }
 
-    pub fn triggers_info(&self) -> Result<Vec<(String, String)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT name, sql FROM sqlite_schema \
-                                          WHERE type='trigger' AND tbl_name=?",
-        )?;
-        let rows = stmt.query_map([&self.name], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    /// Find foreign keys in the CREATE TABLE statement, with the columns each
+    /// one is on (the AST doesn't list the column for column constraints).
+    fn fk_constraints_ast(&self) -> Result<Vec<(Vec<String>, ForeignKeyConstraint)>> {
         let mut res = Vec::new();
-        for result in rows {
-            res.push(result?);
+        if let Ok(ast) = Parser::parse_sql(&SQLiteDialect {}, &self.create_sql()?) {
+            if let Some(Statement::CreateTable(ct)) = ast.first() {
+                for coldef in &ct.columns {
+                    for o in &coldef.options {
+                        if let ColumnOption::ForeignKey(fkc) = &o.option {
+                            res.push((vec![coldef.name.value.clone()], fkc.clone()));
+                        }
+                    }
+                }
+                for constraint in &ct.constraints {
+                    if let TableConstraint::ForeignKey(fkc) = constraint {
+                        let from = fkc.columns.iter().map(|i| i.value.clone()).collect();
+                        res.push((from, fkc.clone()));
+                    }
+                }
+            }
         }
         Ok(res)
     }
 
-    /// Quote the table name if needed to ensure it's a valid identifier
+    /// Find rows which break foreign key constraints, grouped by foreign key.
+    /// Returns (index in `foreign_key_info().list`, rowids) pairs; the rowids
+    /// are None for WITHOUT ROWID tables.
+    pub fn foreign_key_violations(&self) -> Result<Vec<(usize, Vec<Option<i64>>)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT fkid, rowid FROM pragma_foreign_key_check(?, ?) ORDER BY fkid")?;
+        let mut rows = stmt.query([&self.name, &self.schema])?;
+        let mut res: Vec<(usize, Vec<Option<i64>>)> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let fkid: usize = row.get(0)?;
+            let rowid: Option<i64> = row.get(1)?;
+            match res.last_mut() {
+                Some((id, rowids)) if *id == fkid => rowids.push(rowid),
+                _ => res.push((fkid, vec![rowid])),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Find foreign keys in other tables which refer to this one.
+    /// Returns (table name, foreign key) pairs.
+    pub fn referenced_by(&self) -> Result<Vec<(String, ForeignKeyInfo)>> {
+        // Foreign keys can only refer to tables in the same schema
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT m.name FROM {}.sqlite_schema AS m \
+             JOIN pragma_foreign_key_list(m.name, ?1) AS fk \
+             WHERE m.type = 'table' AND fk.\"table\" = ?2 COLLATE NOCASE \
+             ORDER BY m.name",
+            escape_identifier(&self.schema)
+        ))?;
+        let names: Vec<String> = stmt
+            .query_map([&self.schema, &self.name], |r| r.get(0))?
+            .collect::<Result<_>>()?;
+        let mut res = Vec::new();
+        for name in names {
+            let other = Table::new(&self.schema, &name, Rc::clone(&self.conn));
+            for fk in other.foreign_key_info()?.list {
+                if fk.to_table.eq_ignore_ascii_case(&self.name) {
+                    res.push((name.clone(), fk));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Get triggers on this table. Triggers are in the same schema as the
+    /// table, except that TEMP triggers can be on a table in any schema.
+    pub fn triggers_info(&self) -> Result<Vec<TriggerInfo>> {
+        let mut res = Vec::new();
+        let mut schemas = vec![self.schema.clone()];
+        if !self.schema.eq_ignore_ascii_case("temp") {
+            schemas.push("temp".to_string());
+        }
+        for schema in schemas {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT name, sql, tbl_name FROM {}.sqlite_schema \
+                 WHERE type='trigger' AND tbl_name=? COLLATE NOCASE",
+                escape_identifier(&schema)
+            ))?;
+            let mut rows = stmt.query([&self.name])?;
+            while let Some(row) = rows.next()? {
+                let trigger = TriggerInfo {
+                    schema: schema.clone(),
+                    name: row.get(0)?,
+                    sql: row.get(1)?,
+                };
+                let tbl_name: String = row.get(2)?;
+                // A TEMP trigger may be on a table with the same name in another schema
+                let tbl_schema =
+                    trigger_table_schema(&self.conn, &schema, &trigger.sql, &tbl_name)?;
+                if tbl_schema.eq_ignore_ascii_case(&self.schema) {
+                    res.push(trigger);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Quote the table name if needed to ensure it's a valid identifier.
+    /// Tables outside the main database are qualified with the schema name.
     pub fn escaped_name(&self) -> String {
-        // SQLite actually allows $ and any non-ascii character in identifiers
-        // without quoting, but this more restrictive rule is OK for now.
-        // https://www.sqlite.org/draft/tokenreq.html
-        if self
-            .name
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '_')
-            && !keywords::is_keyword(&self.name)
-        {
-            self.name.clone()
+        if self.schema == "main" {
+            escape_identifier(&self.name)
         } else {
-            format!("\"{}\"", &self.name.replace('"', "\"\""))
+            format!(
+                "{}.{}",
+                escape_identifier(&self.schema),
+                escape_identifier(&self.name)
+            )
         }
     }
 
     // Building SQL queries with string formatting is not great, but we can't
     // give the table name as a parameter. Quoting the name *should* work.
 
+    /// Bytes used on disk by this table & its indexes, from the dbstat
+    /// virtual table. Views take up no space.
+    pub fn disk_size(&self) -> Result<u64> {
+        self.conn.query_row(
+            "SELECT coalesce(sum(pgsize), 0) FROM dbstat(?1) \
+             WHERE name = ?2 COLLATE NOCASE \
+             OR name IN (SELECT name FROM pragma_index_list(?2, ?1))",
+            [&self.schema, &self.name],
+            |r| r.get(0),
+        )
+    }
+
+    /// The number of rows the query planner thinks this table has, from
+    /// sqlite_stat1. None if there are no statistics for the table.
+    pub fn stat_rows(&self) -> Result<Option<u64>> {
+        if !has_table(&self.conn, &self.schema, "sqlite_stat1")? {
+            return Ok(None);
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT stat FROM {}.sqlite_stat1 WHERE tbl = ? COLLATE NOCASE",
+            escape_identifier(&self.schema)
+        ))?;
+        let stats = stmt.query_map([&self.name], |r| r.get::<_, String>(0))?;
+        // The first number is the rows in the table, or the entries in an
+        // index, which is fewer for a partial index.
+        let mut res = None;
+        for stat in stats {
+            if let Some(&n) = parse_stat1(&stat?).first() {
+                res = res.max(Some(n));
+            }
+        }
+        Ok(res)
+    }
+
     pub fn count_rows(&self) -> Result<u64> {
         self.conn.query_row(
             &format!("SELECT count(*) from {}", &self.escaped_name()),
@@ -284,6 +887,43 @@ impl Table {
         )
     }
 
+    /// Count the rows, giving up if that's not done by the deadline
+    pub fn count_rows_until(&self, deadline: Instant) -> Result<Option<u64>> {
+        until_deadline(&self.conn, deadline, || self.count_rows())
+    }
+
+    /// Estimate the rows without counting them, from sqlite_stat1 if ANALYZE
+    /// has been run, or else from the pages down one side of the table's
+    /// b-tree, assuming the rest of the tree is similar.
+    /// None for views & virtual tables.
+    pub fn estimate_rows(&self) -> Result<Option<u64>> {
+        if let Some(n) = self.stat_rows()? {
+            return Ok(Some(n));
+        }
+        // The dbstat virtual table may not be available in all SQLite builds
+        Ok(self.btree_estimate().ok().flatten())
+    }
+
+    /// Estimate the rows from the pages on the path from the root of the
+    /// b-tree to its first leaf. dbstat lists pages depth first, so the first
+    /// leaf page ends the path. Each interior page has ncell + 1 children.
+    fn btree_estimate(&self) -> Result<Option<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT pagetype, ncell FROM dbstat(?) WHERE name = ?")?;
+        let mut rows = stmt.query([&self.schema, &self.name])?;
+        let mut estimate: u64 = 1;
+        while let Some(row) = rows.next()? {
+            let ncell: u64 = row.get(1)?;
+            match row.get_ref(0)?.as_str()? {
+                "internal" => estimate = estimate.saturating_mul(ncell + 1),
+                "leaf" => return Ok(Some(estimate.saturating_mul(ncell))),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
     /// Get the AST node for the definition of the named column
     fn col_def_ast(&self, col_name: &str) -> Result<Option<ColumnDef>> {
         if let Ok(ast) = Parser::parse_sql(&SQLiteDialect {}, &self.create_sql()?) {
@@ -298,6 +938,33 @@ impl Table {
         Ok(None)
     }
 
+    /// Get constraints on a column which SQLite's pragmas don't tell us about
+    pub fn column_constraints(&self, col_name: &str) -> Result<ColumnConstraints> {
+        let Some(coldef) = self.col_def_ast(col_name)? else {
+            return Ok(ColumnConstraints::default());
+        };
+        Ok(ColumnConstraints::from_col_def(
+            &coldef,
+            &self.create_sql()?,
+        ))
+    }
+
+    /// Get the expressions of table-level CHECK constraints, as written
+    pub fn table_checks(&self) -> Result<Vec<String>> {
+        let mut res = Vec::new();
+        let sql = self.create_sql()?;
+        if let Ok(ast) = Parser::parse_sql(&SQLiteDialect {}, &sql) {
+            if let Some(Statement::CreateTable(ct)) = ast.first() {
+                for constraint in &ct.constraints {
+                    if let TableConstraint::Check(c) = constraint {
+                        res.push(check_text(&sql, &c.expr));
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
     /// Find & format the AS (?) expression for a generated column
     pub fn get_gencol_expr(&self, col_name: &str) -> Result<String> {
         if let Some(coldef) = self.col_def_ast(col_name)? {
@@ -315,10 +982,31 @@ impl Table {
     }
 }
 
-/// Get the names of tables (inc. virtual tables) in the database.
+/// Quote a table or column name if needed to ensure it's a valid identifier
+pub fn escape_identifier(name: &str) -> String {
+    // SQLite actually allows $ and any non-ascii character in identifiers
+    // without quoting, but this more restrictive rule is OK for now.
+    // https://www.sqlite.org/draft/tokenreq.html
+    if !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && !keywords::is_keyword(name)
+    {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Compare lists of column names, ignoring ASCII case like SQLite
+fn same_names(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+/// Get tables (inc. virtual tables) in all schemas: main, temp & attached.
 /// inc_hidden also includes shadow tables & sqlite_* system tables
-pub fn get_table_names(conn: &Connection, inc_hidden: &bool) -> Result<Vec<String>> {
-    let mut table_names = Vec::new();
+pub fn get_tables(conn: &Rc<Connection>, inc_hidden: &bool) -> Result<Vec<Table>> {
+    let mut tables = Vec::new();
 
     let mut where_clauses = vec!["type IN ('table', 'virtual') AND NOT name LIKE 'sqlite_%'"];
     if *inc_hidden {
@@ -330,24 +1018,347 @@ pub fn get_table_names(conn: &Connection, inc_hidden: &bool) -> Result<Vec<Strin
     }
     for where_clause in where_clauses {
         let mut stmt = conn.prepare(&format!(
-            "SELECT name FROM pragma_table_list WHERE {}",
+            "SELECT schema, name FROM pragma_table_list WHERE {} \
+             ORDER BY (SELECT seq FROM pragma_database_list AS db WHERE db.name = schema)",
             where_clause
         ))?;
-        for name_result in stmt.query_map([], |row| row.get(0))? {
-            table_names.push(name_result?);
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let (schema, name): (String, String) = (row.get(0)?, row.get(1)?);
+            tables.push(Table::new(&schema, &name, Rc::clone(conn)));
+        }
+    }
+    Ok(tables)
+}
+
+/// Find the schema & name of a table, view, index or trigger as it's stored
+/// in the database. Like SQLite, this ignores differences in ASCII upper/lower
+/// case. The name may be qualified with a schema, e.g. `temp.foo`; if not,
+/// schemas are searched in order (main, temp, then attached databases).
+pub fn find_name(conn: &Connection, name: &str) -> Result<Option<(String, String)>> {
+    let schemas = get_schema_names(conn)?;
+    // Table names can contain '.', so try the name as given first
+    if let Some(found) = find_in_schemas(conn, &schemas, name)? {
+        return Ok(Some(found));
+    }
+    if let Some((prefix, rest)) = name.split_once('.') {
+        if let Some(schema) = schemas.iter().find(|s| s.eq_ignore_ascii_case(prefix)) {
+            return find_in_schemas(conn, std::slice::from_ref(schema), rest);
+        }
+    }
+    Ok(None)
+}
+
+fn find_in_schemas(
+    conn: &Connection,
+    schemas: &[String],
+    name: &str,
+) -> Result<Option<(String, String)>> {
+    for schema in schemas {
+        let found: Option<String> = conn
+            .query_row(
+                &format!(
+                    "SELECT name FROM pragma_table_list WHERE schema=?1 AND name=?2 COLLATE NOCASE \
+                     UNION ALL SELECT name FROM {}.sqlite_schema \
+                     WHERE type IN ('index', 'trigger') AND name=?2 COLLATE NOCASE",
+                    escape_identifier(schema)
+                ),
+                [schema, name],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if let Some(found) = found {
+            return Ok(Some((schema.clone(), found)));
+        }
+    }
+    Ok(None)
+}
+
+/// Suggest table & view names similar to one which wasn't found.
+/// Names outside the main database are qualified with their schema.
+pub fn suggest_names(conn: &Connection, name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT iif(schema = 'main', name, schema || '.' || name) FROM pragma_table_list",
+    )?;
+    let name = name.to_lowercase();
+    let max_distance = std::cmp::max(2, name.chars().count() / 3);
+    let mut candidates = Vec::new();
+    for other in stmt.query_map([], |r| r.get::<_, String>(0))? {
+        let other = other?;
+        let distance = edit_distance(&name, &other.to_lowercase());
+        if distance <= max_distance {
+            candidates.push((distance, other));
+        }
+    }
+    candidates.sort();
+    Ok(candidates.into_iter().take(3).map(|(_, n)| n).collect())
+}
+
+/// Match a name against a glob pattern using `*`, `?` and `[...]`, as in
+/// SQLite's GLOB operator, but ignoring ASCII upper/lower case like name lookup.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_ascii_lowercase().chars().collect();
+    let name: Vec<char> = name.to_ascii_lowercase().chars().collect();
+    glob_match_chars(&pattern, &name)
+}
+
+fn glob_match_chars(p: &[char], n: &[char]) -> bool {
+    match p.first() {
+        None => n.is_empty(),
+        Some('*') => (0..=n.len()).any(|i| glob_match_chars(&p[1..], &n[i..])),
+        Some('?') => !n.is_empty() && glob_match_chars(&p[1..], &n[1..]),
+        Some('[') => {
+            let negate = p.get(1) == Some(&'^');
+            let start = if negate { 2 } else { 1 };
+            // A ']' just after the opening '[' or '[^' is part of the set
+            let Some(end) = p
+                .iter()
+                .skip(start + 1)
+                .position(|&c| c == ']')
+                .map(|i| i + start + 1)
+            else {
+                // No closing bracket: match '[' literally
+                return n.first() == Some(&'[') && glob_match_chars(&p[1..], &n[1..]);
+            };
+            let Some(&c) = n.first() else {
+                return false;
+            };
+            let set = &p[start..end];
+            let mut in_set = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == '-' {
+                    in_set |= set[i] <= c && c <= set[i + 2];
+                    i += 3;
+                } else {
+                    in_set |= set[i] == c;
+                    i += 1;
+                }
+            }
+            in_set != negate && glob_match_chars(&p[end + 1..], &n[1..])
         }
+        Some(c) => n.first() == Some(c) && glob_match_chars(&p[1..], &n[1..]),
     }
-    Ok(table_names)
 }
 
-/// Get the names of all views in the database
-pub fn get_view_names(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT name FROM sqlite_schema WHERE type = 'view'")?;
-    let mut rows = stmt.query([])?;
+/// Levenshtein distance between two strings, counting characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Get the type of a named object in a schema: table, view, index or trigger
+pub fn get_object_type(conn: &Connection, schema: &str, name: &str) -> Result<Option<String>> {
+    conn.query_row(
+        &format!(
+            "SELECT type FROM {}.sqlite_schema WHERE name=? COLLATE NOCASE",
+            escape_identifier(schema)
+        ),
+        [name],
+        |r| r.get(0),
+    )
+    .optional()
+}
+
+/// Find an index by name, with the table it's on
+pub fn find_index(
+    conn: &Rc<Connection>,
+    schema: &str,
+    name: &str,
+) -> Result<Option<(Table, IndexInfo)>> {
+    let tbl_name: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT tbl_name FROM {}.sqlite_schema WHERE type='index' AND name=? COLLATE NOCASE",
+                escape_identifier(schema)
+            ),
+            [name],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if let Some(tbl_name) = tbl_name {
+        let table = Table::new(schema, &tbl_name, Rc::clone(conn));
+        if let Some(ix) = table
+            .indexes_info()?
+            .into_iter()
+            .find(|ix| ix.name.eq_ignore_ascii_case(name))
+        {
+            return Ok(Some((table, ix)));
+        }
+    }
+    Ok(None)
+}
+
+/// Order tables so that each one comes after the tables its foreign keys
+/// refer to, e.g. to load data without breaking constraints. Tables in a
+/// reference cycle are kept in their original order.
+pub fn sort_by_dependencies(tables: Vec<Table>) -> Result<Vec<Table>> {
+    let key = |schema: &str, name: &str| (schema.to_lowercase(), name.to_lowercase());
+    let mut remaining = Vec::new();
+    for table in tables {
+        // Foreign keys always refer to tables in the same schema
+        let refers_to: HashSet<(String, String)> = table
+            .foreign_key_info()?
+            .list
+            .iter()
+            .map(|fk| key(&table.schema, &fk.to_table))
+            .filter(|k| *k != key(&table.schema, &table.name))
+            .collect();
+        remaining.push((table, refers_to));
+    }
+
     let mut res = Vec::new();
+    while !remaining.is_empty() {
+        let waiting: HashSet<(String, String)> = remaining
+            .iter()
+            .map(|(t, _)| key(&t.schema, &t.name))
+            .collect();
+        // If every table is waiting for another, there's a cycle: take the first
+        let next = remaining
+            .iter()
+            .position(|(_, refers_to)| refers_to.is_disjoint(&waiting))
+            .unwrap_or(0);
+        res.push(remaining.remove(next).0);
+    }
+    Ok(res)
+}
+
+/// Run SQLite's integrity_check, or the faster quick_check, on one schema.
+/// Returns the problems found, which is empty if the database is OK.
+pub fn integrity_check(conn: &Connection, schema: &str, quick: bool) -> Result<Vec<String>> {
+    let pragma = if quick {
+        "quick_check"
+    } else {
+        "integrity_check"
+    };
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.{}", escape_identifier(schema), pragma))?;
+    let messages: Vec<String> = stmt.query_map([], |r| r.get(0))?.collect::<Result<_>>()?;
+    Ok(if messages == ["ok"] {
+        Vec::new()
+    } else {
+        messages
+    })
+}
+
+/// Space used by one b-tree (a table or an index), from dbstat
+#[derive(Debug)]
+pub struct BtreeSize {
+    pub name: String,
+    /// 'table' or 'index'
+    pub obj_type: String,
+    /// The table an index is on; the same as name for tables
+    pub tbl_name: String,
+    pub pages: u64,
+    pub bytes: u64,
+    pub unused_bytes: u64,
+    pub overflow_pages: u64,
+}
+
+/// Get the space used by each table & index in a schema, biggest first.
+/// This needs the dbstat virtual table, which SQLite may be built without.
+pub fn btree_sizes(conn: &Connection, schema: &str) -> Result<Vec<BtreeSize>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT s.name, coalesce(m.type, 'table'), coalesce(m.tbl_name, s.name), \
+         count(*), sum(s.pgsize), sum(s.unused), sum(s.pagetype = 'overflow') \
+         FROM dbstat(?) AS s LEFT JOIN {}.sqlite_schema AS m ON m.name = s.name \
+         GROUP BY s.name ORDER BY sum(s.pgsize) DESC, s.name",
+        escape_identifier(schema)
+    ))?;
+    let rows = stmt.query_map([schema], |r| {
+        Ok(BtreeSize {
+            name: r.get(0)?,
+            obj_type: r.get(1)?,
+            tbl_name: r.get(2)?,
+            pages: r.get(3)?,
+            bytes: r.get(4)?,
+            unused_bytes: r.get(5)?,
+            overflow_pages: r.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Does a table exist in this schema? Names are matched exactly.
+pub fn has_table(conn: &Connection, schema: &str, name: &str) -> Result<bool> {
+    let count: usize = conn.query_row(
+        "SELECT count(*) FROM pragma_table_list WHERE schema = ? AND name = ?",
+        [schema, name],
+        |r| r.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Find the schema of the table or view a trigger is on. That's the schema
+/// the trigger is in, except that TEMP triggers can be on a table in any
+/// schema. sqlite_schema only records the table name, so the schema comes
+/// from the trigger's SQL, or is found like SQLite does for unqualified
+/// names: temp, then main, then attached databases.
+pub fn trigger_table_schema(
+    conn: &Connection,
+    schema: &str,
+    sql: &str,
+    tbl_name: &str,
+) -> Result<String> {
+    if !schema.eq_ignore_ascii_case("temp") {
+        return Ok(schema.to_string());
+    }
+    let mut schemas = get_schema_names(conn)?;
+    if let Ok(ast) = Parser::parse_sql(&SQLiteDialect {}, sql) {
+        if let Some(Statement::CreateTrigger(ct)) = ast.first() {
+            if let [qualifier, _] = ct.table_name.0.as_slice() {
+                if let Some(found) = qualifier
+                    .as_ident()
+                    .and_then(|q| schemas.iter().find(|s| s.eq_ignore_ascii_case(&q.value)))
+                {
+                    return Ok(found.clone());
+                }
+            }
+        }
+    }
+    schemas.sort_by_key(|s| s != "temp");
+    for s in schemas {
+        let count: usize = conn.query_row(
+            "SELECT count(*) FROM pragma_table_list WHERE schema = ? AND name = ? COLLATE NOCASE",
+            [&s, tbl_name],
+            |r| r.get(0),
+        )?;
+        if count > 0 {
+            return Ok(s);
+        }
+    }
+    Ok(schema.to_string())
+}
+
+/// Get the names of the schemas on this connection: 'main', 'temp' and
+/// any attached databases
+pub fn get_schema_names(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_database_list ORDER BY seq")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
 
-    while let Some(row) = rows.next()? {
-        res.push(row.get(0)?)
+/// Get all views in all schemas
+pub fn get_views(conn: &Rc<Connection>) -> Result<Vec<Table>> {
+    let mut res = Vec::new();
+    for schema in get_schema_names(conn)? {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT name FROM {}.sqlite_schema WHERE type = 'view'",
+            escape_identifier(&schema)
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get(0)?;
+            res.push(Table::new(&schema, &name, Rc::clone(conn)));
+        }
     }
     Ok(res)
 }
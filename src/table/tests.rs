@@ -1,8 +1,13 @@
 #![cfg(test)]
+#![allow(clippy::bool_assert_comparison)]
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use super::{get_table_names, Table};
-use anyhow;
+use super::{
+    btree_sizes, edit_distance, find_index, find_name, get_object_type, get_tables, get_views,
+    glob_match, has_table, integrity_check, sort_by_dependencies, suggest_names, ColumnConstraints,
+    RowCount, Table, TriggerInfo,
+};
 use rusqlite::Connection;
 
 const SCHEMA: &str = r#"
@@ -30,6 +35,17 @@ CREATE TABLE gen_cols (
     hexadec GENERATED ALWAYS AS (hex(a))
 );
 CREATE VIRTUAL TABLE email USING fts5(sender, title, body);
+CREATE TABLE constrained (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL ON CONFLICT IGNORE DEFAULT 'x' COLLATE NOCASE CHECK (length(name) > 0),
+    n INT DEFAULT -1,
+    added DEFAULT (unixepoch()),
+    CHECK (n != 0)
+);
+CREATE TABLE child (
+    parent_id REFERENCES constrained (id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED
+);
+CREATE INDEX constrained_expr ON constrained (lower(name) COLLATE NOCASE, n DESC) WHERE n > 0;
 "#;
 
 #[test]
@@ -37,31 +53,160 @@ fn lookup() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let t1 = Table::new("t1", Rc::clone(&conn));
+    let t1 = Table::new("main", "t1", Rc::clone(&conn));
     assert_eq!(t1.in_db()?, true);
     assert_eq!(t1.obj_type()?, "table");
 
-    let v1 = Table::new("v1", Rc::clone(&conn));
+    let v1 = Table::new("main", "v1", Rc::clone(&conn));
     assert_eq!(v1.in_db()?, true);
     assert_eq!(v1.obj_type()?, "view");
 
-    assert_eq!(Table::new("nonesuch", Rc::clone(&conn)).in_db()?, false);
+    assert_eq!(
+        Table::new("main", "nonesuch", Rc::clone(&conn)).in_db()?,
+        false
+    );
+    Ok(())
+}
+
+#[test]
+fn lookup_other_objects() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+
+    assert_eq!(
+        get_object_type(&conn, "main", "t1_a")?,
+        Some("index".to_owned())
+    );
+    assert_eq!(
+        get_object_type(&conn, "main", "recip_insert")?,
+        Some("trigger".to_owned())
+    );
+    assert_eq!(get_object_type(&conn, "main", "nonesuch")?, None);
+
+    let (table, ix) = find_index(&conn, "main", "t1_a")?.unwrap();
+    assert_eq!(table.name, "t1");
+    assert!(ix.unique);
+    assert!(ix
+        .create_sql(&conn)?
+        .unwrap()
+        .contains("CREATE UNIQUE INDEX"));
+    assert!(find_index(&conn, "main", "t1")?.is_none());
     Ok(())
 }
 
+#[test]
+fn case_insensitive_lookup() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+
+    let main = |n: &str| Some(("main".to_owned(), n.to_owned()));
+    assert_eq!(find_name(&conn, "T1")?, main("t1"));
+    assert_eq!(find_name(&conn, "Gen_Cols")?, main("gen_cols"));
+    assert_eq!(find_name(&conn, "T1_A")?, main("t1_a"));
+    assert_eq!(find_name(&conn, "nonesuch")?, None);
+    assert!(Table::new("main", "V1", Rc::clone(&conn)).in_db()?);
+
+    assert_eq!(suggest_names(&conn, "gen_col")?, ["gen_cols"]);
+    assert_eq!(suggest_names(&conn, "CONSTRAIND")?, ["constrained"]);
+    assert!(suggest_names(&conn, "nonesuch")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn attached_schema() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    conn.execute_batch(
+        "ATTACH ':memory:' AS logs;
+         CREATE TABLE logs.entries (t1_a REFERENCES t1_ref (a), msg TEXT);
+         CREATE TABLE logs.t1_ref (a INT);
+         CREATE INDEX logs.entries_msg ON entries (msg);
+         CREATE VIEW logs.recent AS SELECT * FROM entries;
+         CREATE TABLE logs.t1 (b);
+         INSERT INTO logs.t1 VALUES (1), (2);",
+    )?;
+
+    assert_eq!(
+        find_name(&conn, "Logs.Entries")?,
+        Some(("logs".to_owned(), "entries".to_owned()))
+    );
+    // Unqualified names look in main first
+    assert_eq!(
+        find_name(&conn, "t1")?,
+        Some(("main".to_owned(), "t1".to_owned()))
+    );
+    assert_eq!(
+        find_name(&conn, "logs.t1")?,
+        Some(("logs".to_owned(), "t1".to_owned()))
+    );
+    assert_eq!(find_name(&conn, "logs.t1_a")?, None);
+    assert_eq!(suggest_names(&conn, "logs.entry")?, ["logs.entries"]);
+    assert_eq!(
+        get_object_type(&conn, "logs", "entries_msg")?,
+        Some("index".to_owned())
+    );
+
+    let t = Table::new("logs", "t1", Rc::clone(&conn));
+    assert_eq!(t.escaped_name(), "logs.t1");
+    assert_eq!(t.count_rows()?, 2);
+    assert_eq!(t.columns_info()?[0].name, "b");
+    assert!(t.create_sql()?.contains("(b)"));
+
+    let (table, ix) = find_index(&conn, "logs", "entries_msg")?.unwrap();
+    assert_eq!(table.schema, "logs");
+    assert_eq!(ix.column_names(&conn)?, ["msg"]);
+    assert!(ix.create_sql(&conn)?.is_some());
+
+    let refs = Table::new("logs", "t1_ref", Rc::clone(&conn)).referenced_by()?;
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].0, "entries");
+
+    assert!(get_tables(&conn, &false)?
+        .iter()
+        .any(|t| t.schema == "logs" && t.name == "entries"));
+    assert!(get_views(&conn)?
+        .iter()
+        .any(|v| v.escaped_name() == "logs.recent"));
+    Ok(())
+}
+
+#[test]
+fn edit_distances() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("abc", "abc"), 0);
+    assert_eq!(edit_distance("abc", "ab"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "ab"), 2);
+}
+
+#[test]
+fn glob_patterns() {
+    assert!(glob_match("order*", "orders_item"));
+    assert!(glob_match("ORDER*", "order"));
+    assert!(!glob_match("order*", "my_order"));
+    assert!(glob_match("*_log", "app_log"));
+    assert!(glob_match("t?", "t1"));
+    assert!(!glob_match("t?", "t"));
+    assert!(glob_match("t[0-9]", "t5"));
+    assert!(!glob_match("t[^0-9]", "t5"));
+    assert!(glob_match("[]]x", "]x"));
+    assert!(glob_match("a[b", "a[b"));
+    assert!(glob_match("*", ""));
+}
+
 #[test]
 fn escape_name() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let t1 = Table::new("t1", Rc::clone(&conn));
+    let t1 = Table::new("main", "t1", Rc::clone(&conn));
     assert_eq!(t1.escaped_name(), "t1");
 
-    let t = Table::new("select", Rc::clone(&conn));
+    let t = Table::new("main", "select", Rc::clone(&conn));
     assert_eq!(t.escaped_name(), "\"select\"");
     assert_eq!(t.count_rows()?, 0);
 
-    let t = Table::new("foo \n\"bar", Rc::clone(&conn));
+    let t = Table::new("main", "foo \n\"bar", Rc::clone(&conn));
     assert!(t.in_db()?);
     assert_eq!(t.escaped_name(), "\"foo \n\"\"bar\"");
     assert_eq!(t.count_rows()?, 0);
@@ -74,7 +219,7 @@ fn foreign_keys() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let fk = Table::new("select", Rc::clone(&conn)).foreign_key_info()?;
+    let fk = Table::new("main", "select", Rc::clone(&conn)).foreign_key_info()?;
     assert_eq!(fk.for_name("a"), None);
     if let Some(fki) = fk.multicolumn().first() {
         assert_eq!(fki.to_table, "multi_pk");
@@ -83,6 +228,83 @@ fn foreign_keys() -> anyhow::Result<()> {
     } else {
         unreachable!();
     }
+
+    let fk = Table::new("main", "child", Rc::clone(&conn)).foreign_key_info()?;
+    let fki = fk.for_name("parent_id").unwrap();
+    assert_eq!(fki.on_delete, "CASCADE");
+    assert_eq!(fki.on_update, "NO ACTION");
+    assert_eq!(
+        fki.deferrable,
+        Some("DEFERRABLE INITIALLY DEFERRED".to_owned())
+    );
+
+    // Two foreign keys on the same column
+    conn.execute_batch(
+        "CREATE TABLE t2 (id);
+         CREATE TABLE two_fks (
+             x REFERENCES t1 (a) ON DELETE CASCADE,
+             FOREIGN KEY (x) REFERENCES T2 (rowid) MATCH FULL
+         );",
+    )?;
+    let fk = Table::new("main", "two_fks", Rc::clone(&conn)).foreign_key_info()?;
+    assert_eq!(fk.list.len(), 2);
+    for fki in &fk.list {
+        match fki.to_table.as_str() {
+            "t1" => {
+                assert_eq!(fki.on_delete, "CASCADE");
+                assert_eq!(fki.match_kind, None);
+            }
+            "T2" => {
+                assert_eq!(fki.to, ["rowid"]);
+                assert_eq!(fki.match_kind, Some("MATCH FULL".to_owned()));
+            }
+            other => unreachable!("{}", other),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn referenced_by() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+
+    let refs = Table::new("main", "multi_pk", Rc::clone(&conn)).referenced_by()?;
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].0, "select");
+    assert_eq!(refs[0].1.from, ["a", "b"]);
+
+    assert!(Table::new("main", "t1", Rc::clone(&conn))
+        .referenced_by()?
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn consistency_checks() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    assert!(integrity_check(&conn, "main", false)?.is_empty());
+    assert!(integrity_check(&conn, "main", true)?.is_empty());
+
+    // Turn off enforcing foreign keys to insert rows which break them
+    conn.execute_batch(
+        "PRAGMA foreign_keys = OFF;
+         INSERT INTO constrained (id, name) VALUES (1, 'a');
+         INSERT INTO child VALUES (1), (2), (3), (NULL);
+         INSERT INTO multi_pk VALUES (1, 2, 3);
+         INSERT INTO \"select\" VALUES (7, 1, 2), (8, 2, 1);",
+    )?;
+    let child = Table::new("main", "child", Rc::clone(&conn));
+    assert_eq!(
+        child.foreign_key_violations()?,
+        [(0, vec![Some(2), Some(3)])]
+    );
+    let t = Table::new("main", "select", Rc::clone(&conn));
+    assert_eq!(t.foreign_key_violations()?, [(0, vec![Some(8)])]);
+    assert!(Table::new("main", "t1", Rc::clone(&conn))
+        .foreign_key_violations()?
+        .is_empty());
     Ok(())
 }
 
@@ -91,11 +313,174 @@ fn index_cols() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let iis = Table::new("t1", Rc::clone(&conn)).indexes_info()?;
+    let iis = Table::new("main", "t1", Rc::clone(&conn)).indexes_info()?;
     let ii = iis.first().unwrap();
     assert_eq!(ii.name, "t1_a");
     assert_eq!(ii.unique, true);
     assert_eq!(ii.column_names(&conn)?, ["a"]);
+    assert_eq!(ii.where_clause(&conn)?, None);
+    Ok(())
+}
+
+#[test]
+fn index_details() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+
+    let iis = Table::new("main", "constrained", Rc::clone(&conn)).indexes_info()?;
+    let ii = iis.iter().find(|ii| ii.name == "constrained_expr").unwrap();
+    assert!(ii.partial);
+    assert_eq!(ii.where_clause(&conn)?, Some("n > 0".to_owned()));
+    let cols = ii.columns(&conn)?;
+    assert_eq!(cols.len(), 2);
+    assert_eq!(cols[0].name, "lower(name)");
+    assert!(cols[0].is_expr);
+    assert_eq!(cols[0].collation, "NOCASE");
+    assert_eq!(cols[1].name, "n");
+    assert!(cols[1].desc);
+    assert!(!cols[1].is_plain(None));
+    Ok(())
+}
+
+#[test]
+fn without_rowid_pk() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch("CREATE TABLE kv (k TEXT PRIMARY KEY, v) WITHOUT ROWID;")?;
+
+    // The primary key index has no entry of its own in sqlite_schema
+    let iis = Table::new("main", "kv", Rc::clone(&conn)).indexes_info()?;
+    let ii = iis.first().unwrap();
+    assert_eq!(ii.origin, "pk");
+    assert_eq!(ii.create_sql(&conn)?, None);
+    assert_eq!(ii.where_clause(&conn)?, None);
+    assert_eq!(ii.column_names(&conn)?, ["k"]);
+    Ok(())
+}
+
+#[test]
+fn dependency_order() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    conn.execute_batch(
+        "CREATE TABLE a_first (x REFERENCES b_second (y));
+         CREATE TABLE b_second (y PRIMARY KEY, z REFERENCES b_second (y));
+         CREATE TABLE cycle1 (x REFERENCES cycle2 (x));
+         CREATE TABLE cycle2 (x REFERENCES cycle1 (x));",
+    )?;
+
+    let mut tables = get_tables(&conn, &false)?;
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    let names: Vec<String> = sort_by_dependencies(tables)?
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    let pos = |n: &str| names.iter().position(|x| x == n).unwrap();
+    assert!(pos("b_second") < pos("a_first"));
+    assert!(pos("constrained") < pos("child"));
+    assert!(pos("multi_pk") < pos("select"));
+    assert!(pos("cycle1") < pos("cycle2"));
+    assert_eq!(names.len(), 12);
+    Ok(())
+}
+
+#[test]
+fn disk_size() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    conn.execute_batch(
+        "WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 2000) \
+         INSERT INTO t1 SELECT n FROM s",
+    )?;
+
+    let t1 = Table::new("main", "t1", Rc::clone(&conn));
+    let v1 = Table::new("main", "v1", Rc::clone(&conn));
+    let multi_pk = Table::new("main", "multi_pk", Rc::clone(&conn));
+    // t1 has data in the table & its index, multi_pk has one empty page each
+    assert!(t1.disk_size()? > multi_pk.disk_size()?);
+    assert!(multi_pk.disk_size()? > 0);
+    assert_eq!(v1.disk_size()?, 0);
+    Ok(())
+}
+
+#[test]
+fn btree_size_breakdown() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    conn.execute_batch(
+        "CREATE TABLE big (data BLOB);
+         INSERT INTO big VALUES (zeroblob(20000));",
+    )?;
+
+    let sizes = btree_sizes(&conn, "main")?;
+    let big = &sizes[0];
+    assert_eq!(big.name, "big");
+    assert_eq!(big.obj_type, "table");
+    assert!(big.overflow_pages > 0);
+    assert!(big.bytes >= 20000);
+
+    let ix = sizes.iter().find(|s| s.name == "t1_a").unwrap();
+    assert_eq!(ix.obj_type, "index");
+    assert_eq!(ix.tbl_name, "t1");
+    assert_eq!(ix.pages, 1);
+    assert!(sizes.iter().any(|s| s.name == "sqlite_schema"));
+    Ok(())
+}
+
+#[test]
+fn row_estimates() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(
+        "CREATE TABLE big (n INTEGER PRIMARY KEY, data);
+         WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 5000) \
+         INSERT INTO big SELECT n, randomblob(50) FROM s;
+         CREATE VIEW big_v AS SELECT * FROM big;",
+    )?;
+    let big = Table::new("main", "big", Rc::clone(&conn));
+    let later = Instant::now() + Duration::from_secs(60);
+    assert_eq!(big.count_rows_until(later)?, Some(5000));
+    assert_eq!(big.count_rows_until(Instant::now())?, None);
+    // The connection still works after a count is abandoned
+    assert_eq!(big.count_rows()?, 5000);
+
+    // Estimated from the b-tree, so it's only roughly right
+    let estimate = big.estimate_rows()?.unwrap();
+    assert!((2500..10000).contains(&estimate), "{}", estimate);
+    conn.execute_batch("ANALYZE")?;
+    assert_eq!(big.estimate_rows()?, Some(5000));
+    let view = Table::new("main", "big_v", Rc::clone(&conn));
+    assert_eq!(view.estimate_rows()?, None);
+
+    assert_eq!(RowCount::Exact(5).to_string(), "5");
+    assert_eq!(RowCount::Approx(5).to_string(), "~5");
+    assert_eq!(RowCount::Unknown.to_string(), "?");
+    Ok(())
+}
+
+#[test]
+fn planner_stats() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+    let t1 = Table::new("main", "t1", Rc::clone(&conn));
+    let ix = t1.indexes_info()?.into_iter().next().unwrap();
+    assert!(!has_table(&conn, "main", "sqlite_stat1")?);
+    assert_eq!(t1.stat_rows()?, None);
+    assert_eq!(ix.stats(&conn)?, None);
+
+    conn.execute_batch(
+        "WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 50) \
+         INSERT INTO t1 SELECT n FROM s;
+         ANALYZE;
+         INSERT INTO t1 VALUES (51);",
+    )?;
+    assert_eq!(t1.stat_rows()?, Some(50));
+    let stats = ix.stats(&conn)?.unwrap();
+    assert_eq!(stats.entries, 50);
+    assert_eq!(stats.per_key, [1]);
+    // sqlite_stat4 is only made if SQLite is built with SQLITE_ENABLE_STAT4
+    assert_eq!(
+        stats.samples.is_some(),
+        has_table(&conn, "main", "sqlite_stat4")?
+    );
     Ok(())
 }
 
@@ -104,7 +489,7 @@ fn generated_cols() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let t = Table::new("gen_cols", Rc::clone(&conn));
+    let t = Table::new("main", "gen_cols", Rc::clone(&conn));
     assert_eq!(t.get_gencol_expr("square")?, "a * a");
     assert_eq!(t.get_gencol_expr("hexadec")?, "hex(a)");
     Ok(())
@@ -115,24 +500,30 @@ fn virtual_table() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let non_hidden_tbls = get_table_names(&conn, &false)?;
+    let names = |inc_hidden| -> anyhow::Result<Vec<String>> {
+        Ok(get_tables(&conn, inc_hidden)?
+            .into_iter()
+            .map(|t| t.name)
+            .collect())
+    };
+    let non_hidden_tbls = names(&false)?;
     assert!(non_hidden_tbls.contains(&"email".to_owned()));
     assert!(!non_hidden_tbls.contains(&"email_data".to_owned()));
-    assert!(!non_hidden_tbls.contains(&"sqlite_master".to_owned()));
+    assert!(!non_hidden_tbls.contains(&"sqlite_schema".to_owned()));
 
-    let all_tbls = get_table_names(&conn, &false)?;
+    let all_tbls = names(&true)?;
     assert!(all_tbls.contains(&"email".to_owned()));
-    assert!(!all_tbls.contains(&"email_data".to_owned()));
-    assert!(!all_tbls.contains(&"sqlite_master".to_owned()));
+    assert!(all_tbls.contains(&"email_data".to_owned()));
+    assert!(all_tbls.contains(&"sqlite_schema".to_owned()));
 
-    let t = Table::new("email", Rc::clone(&conn));
+    let t = Table::new("main", "email", Rc::clone(&conn));
     assert_eq!(t.virtual_using()?, Some("fts5".to_owned()));
     assert!(!t.is_shadow()?);
 
-    let t2 = Table::new("gen_cols", Rc::clone(&conn));
+    let t2 = Table::new("main", "gen_cols", Rc::clone(&conn));
     assert_eq!(t2.virtual_using()?, None);
 
-    let st = Table::new("email_config", Rc::clone(&conn));
+    let st = Table::new("main", "email_config", Rc::clone(&conn));
     assert!(st.is_shadow()?);
     Ok(())
 }
@@ -142,12 +533,141 @@ fn trigger() -> anyhow::Result<()> {
     let conn = Rc::new(Connection::open_in_memory()?);
     conn.execute_batch(SCHEMA)?;
 
-    let v1 = Table::new("v1", Rc::clone(&conn));
+    let v1 = Table::new("main", "v1", Rc::clone(&conn));
     let triggers = v1.triggers_info()?;
     assert_eq!(triggers.len(), 1);
-    let (name, sql) = triggers.first().unwrap();
-    assert_eq!(name, "recip_insert");
-    assert!(sql.contains("CREATE TRIGGER"));
+    let trigger = triggers.first().unwrap();
+    assert_eq!(trigger.name, "recip_insert");
+    assert_eq!(trigger.schema, "main");
+    assert!(trigger.sql.contains("CREATE TRIGGER"));
+
+    conn.execute_batch(
+        "CREATE TEMP TRIGGER t1_check BEFORE INSERT ON main.t1 BEGIN SELECT 1; END;",
+    )?;
+    let triggers = Table::new("main", "t1", Rc::clone(&conn)).triggers_info()?;
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(triggers[0].name, "t1_check");
+    assert_eq!(triggers[0].schema, "temp");
+
+    // A TEMP trigger on a table with the same name in another schema
+    conn.execute_batch(
+        "ATTACH ':memory:' AS logs;
+         CREATE TABLE logs.t1 (a);
+         CREATE TEMP TRIGGER logs_check AFTER DELETE ON LOGS.t1 BEGIN SELECT 1; END;
+         CREATE TABLE logs.t2 (a);
+         CREATE TEMP TRIGGER t2_check AFTER DELETE ON t2 BEGIN SELECT 1; END;",
+    )?;
+    let triggers = Table::new("main", "t1", Rc::clone(&conn)).triggers_info()?;
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(triggers[0].name, "t1_check");
+    let triggers = Table::new("logs", "t1", Rc::clone(&conn)).triggers_info()?;
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(triggers[0].name, "logs_check");
+    // Unqualified, but there's only one t2 it can be on
+    let triggers = Table::new("logs", "t2", Rc::clone(&conn)).triggers_info()?;
+    assert_eq!(triggers.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn trigger_parts() {
+    let parts = |sql: &str| {
+        TriggerInfo {
+            schema: "main".to_owned(),
+            name: "trg".to_owned(),
+            sql: sql.to_owned(),
+        }
+        .parts()
+    };
+    let stmts = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        parts("CREATE TRIGGER trg BEFORE INSERT ON t1 BEGIN SELECT RAISE(ABORT, 'it''s'); END"),
+        Some((
+            "BEFORE INSERT".to_owned(),
+            stmts(&["SELECT RAISE(ABORT, 'it''s')"])
+        ))
+    );
+    assert_eq!(
+        parts(
+            "CREATE TRIGGER IF NOT EXISTS main.trg INSTEAD OF INSERT ON \"my view\"
+             WHEN NEW.recip_a != 0
+             BEGIN
+                 -- Store the reciprocal
+                 INSERT INTO t1(a) VALUES (1.0/NEW.recip_a);
+             END"
+        ),
+        Some((
+            "INSTEAD OF INSERT WHEN NEW.recip_a != 0".to_owned(),
+            stmts(&["INSERT INTO t1(a) VALUES (1.0/NEW.recip_a)"])
+        ))
+    );
+    // Several columns, FOR EACH ROW, and a CASE ... END in the body
+    assert_eq!(
+        parts(
+            "CREATE TEMP TRIGGER trg AFTER UPDATE OF a, b ON main.t1 FOR EACH ROW
+             WHEN old.a <> new.a BEGIN
+                 UPDATE t2 SET c = CASE WHEN new.b THEN 1 END;
+                 DELETE FROM t3;
+             END;"
+        ),
+        Some((
+            "AFTER UPDATE OF a, b FOR EACH ROW WHEN old.a <> new.a".to_owned(),
+            stmts(&[
+                "UPDATE t2 SET c = CASE WHEN new.b THEN 1 END",
+                "DELETE FROM t3"
+            ])
+        ))
+    );
+    assert_eq!(
+        parts("CREATE TRIGGER trg DELETE ON t1 BEGIN DELETE FROM y; END"),
+        Some(("DELETE".to_owned(), stmts(&["DELETE FROM y"])))
+    );
+    assert_eq!(parts("CREATE TRIGGER trg"), None);
+}
+
+#[test]
+fn column_constraints() -> anyhow::Result<()> {
+    let conn = Rc::new(Connection::open_in_memory()?);
+    conn.execute_batch(SCHEMA)?;
+
+    let t = Table::new("main", "constrained", Rc::clone(&conn));
+    let defaults: Vec<Option<String>> = t.columns_info()?.iter().map(|c| c.default_sql()).collect();
+    assert_eq!(
+        defaults,
+        [
+            None,
+            Some("'x'".to_owned()),
+            Some("-1".to_owned()),
+            Some("(unixepoch())".to_owned())
+        ]
+    );
+
+    assert!(t.column_constraints("id")?.autoincrement);
+    let name_cons = t.column_constraints("name")?;
+    assert_eq!(name_cons.collation, Some("NOCASE".to_owned()));
+    assert_eq!(name_cons.checks, ["length(name) > 0"]);
+    assert_eq!(
+        name_cons.not_null_conflict,
+        Some("ON CONFLICT IGNORE".to_owned())
+    );
+    assert_eq!(t.column_constraints("n")?, ColumnConstraints::default());
+    assert_eq!(t.table_checks()?, ["n != 0"]);
 
+    // CHECK constraints are shown as written, even over several lines
+    conn.execute_batch(
+        "CREATE TABLE checked (
+             é TEXT CHECK (é IN ('a', 'b''s')),
+             CHECK (é != 'c'
+                    OR é IS NULL)
+         );",
+    )?;
+    let t = Table::new("main", "checked", Rc::clone(&conn));
+    assert_eq!(t.column_constraints("é")?.checks, ["é IN ('a', 'b''s')"]);
+    assert_eq!(
+        t.table_checks()?,
+        ["é != 'c'\n                    OR é IS NULL"]
+    );
     Ok(())
 }
@@ -0,0 +1,134 @@
+#![cfg(test)]
+use std::time::{Duration, Instant};
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use super::{
+    check_schema, db_summary, fallbacks, fmt_value, parse_seconds, truncate_text, CountMode,
+    RowCounter,
+};
+
+#[test]
+fn truncate() {
+    assert_eq!(truncate_text("short", 5, 20), "short");
+    assert_eq!(truncate_text("two\r\nlines\n", 12, 20), "two↵lines↵");
+    let long = "a long line\nof text which goes on";
+    assert_eq!(truncate_text(long, 33, 20), "a long l… (33 chars)");
+    // Too narrow for the length to fit
+    assert_eq!(truncate_text(long, 33, 12), "a long line…");
+    assert_eq!(truncate_text(long, 33, 1), "…");
+    for max_chars in 1..40 {
+        assert!(truncate_text(long, 33, max_chars).chars().count() <= max_chars);
+    }
+}
+
+#[test]
+fn format_values() {
+    let text = |s: &str| Value::Text(s.to_owned());
+    assert_eq!(fmt_value(Value::Null, 0, None), "");
+    assert_eq!(fmt_value(Value::Integer(42), 2, Some(1)), "42");
+    // Line breaks are shown as ↵ with or without a width limit
+    assert_eq!(fmt_value(text("a\nb"), 3, None), "a↵b");
+    assert_eq!(fmt_value(text("a\nb"), 3, Some(10)), "a↵b");
+    assert_eq!(fmt_value(text("x\ny"), 5000, None), "x↵y… (5000 chars)");
+    assert_eq!(fmt_value(text("abcdefghij"), 5000, Some(10)), "abcdefghi…");
+    assert_eq!(
+        fmt_value(Value::Blob(vec![0; 20]), 20, None),
+        "b\"\\x00\\x00\\x00\\x00\\x00\\x00\".. (20 B)"
+    );
+    // Text fetched in full isn't marked as cut short
+    let long = "a".repeat(5000);
+    assert_eq!(fmt_value(text(&long), 5000, None), long);
+    // Blobs fit the width limit too
+    let blob = || Value::Blob(vec![0; 8]);
+    assert_eq!(
+        fmt_value(blob(), 100_000, Some(30)),
+        "b\"\\x00\\x00\\x00\".. (97.7 KiB)"
+    );
+    assert_eq!(fmt_value(blob(), 100_000, Some(5)), "…");
+    assert_eq!(
+        fmt_value(Value::Blob(vec![1, 2, 3, 4]), 4, Some(15)),
+        "b\"\\x01\".. (4 B)"
+    );
+}
+
+#[test]
+fn seconds() {
+    assert_eq!(parse_seconds("2.5"), Ok(Duration::from_millis(2500)));
+    assert!(parse_seconds("-1").is_err());
+    assert!(parse_seconds("soon").is_err());
+    // Very long timeouts are capped, so they can be added to the current time
+    let long = parse_seconds("1e19").unwrap();
+    assert!(Instant::now().checked_add(long).is_some());
+}
+
+#[test]
+fn summary() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("sqlite-glance-{}.db", std::process::id()));
+    let conn = Connection::open(&path)?;
+    conn.execute_batch(
+        "PRAGMA user_version = 7;
+         CREATE TABLE a (x);
+         INSERT INTO a VALUES (1), (2), (3);
+         CREATE TABLE b (y);
+         INSERT INTO b VALUES (1), (2);",
+    )?;
+    let summary = |mode| {
+        db_summary(
+            &path,
+            &RowCounter::new(mode, Instant::now() + Duration::from_secs(5)),
+        )
+    };
+    let cells = summary(CountMode::Exact)?;
+    assert_eq!(cells[1..], ["4096", "2", "5", "delete", "7"]);
+    assert_eq!(summary(CountMode::None)?[3], "?");
+    assert!(summary(CountMode::Estimate)?[3].starts_with('~'));
+
+    // A table which can't be counted is left out of the total
+    conn.execute_batch(
+        "PRAGMA writable_schema = ON;
+         INSERT INTO sqlite_schema VALUES
+            ('table', 'geo', 'geo', 0, 'CREATE VIRTUAL TABLE geo USING nosuchmodule(x)');",
+    )?;
+    drop(conn);
+    let cells = summary(CountMode::Exact)?;
+    assert_eq!(cells[2..4], ["3", "~5"]);
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn check_with_missing_function() -> anyhow::Result<()> {
+    // An index on a function another application defines
+    let uri = "file:check_with_missing_function?mode=memory&cache=shared";
+    let app_conn = Connection::open(uri)?;
+    app_conn.create_scalar_function(
+        "myfunc",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(ctx.get::<i64>(0)? * 2),
+    )?;
+    app_conn.execute_batch(
+        "CREATE TABLE a (x);
+         INSERT INTO a VALUES (1), (2);
+         CREATE INDEX a_f ON a(myfunc(x));",
+    )?;
+
+    // With the stand-in function, the index doesn't match the table
+    let conn = Connection::open(uri)?;
+    fallbacks::register_fallbacks(&conn, true)?;
+    let (pragma, _, problems) = check_schema(&conn, "main", false)?;
+    assert_eq!(pragma, "integrity_check");
+    assert_eq!(problems.len(), 2);
+
+    // Without it, only the quick check can run, and the file is fine
+    let conn = Connection::open(uri)?;
+    fallbacks::register_fallbacks(&conn, false)?;
+    let (pragma, skipped, problems) = check_schema(&conn, "main", false)?;
+    assert_eq!(pragma, "quick_check");
+    assert!(skipped.is_some_and(|e| e.contains("myfunc")));
+    assert!(problems.is_empty());
+    Ok(())
+}